@@ -0,0 +1,165 @@
+//! # Base64
+//!
+//! Binary data is usually embedded in JSON as a base64 string. It can be decoded while parsing with
+//! [`ParseString::read_base64`](crate::parse::ParseString::read_base64),
+//! and encoded while emitting with [`EmitString::base64`](crate::emit::EmitString::base64).
+
+use crate::parse::SyntaxError;
+
+/// The base64 alphabets of RFC 4648
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Alphabet {
+    /// Uses `+` and `/` for the last two symbols.
+    #[default]
+    Standard,
+    /// Uses `-` and `_` for the last two symbols, so the text is safe to use in URLs and file names.
+    UrlSafe,
+}
+
+impl Alphabet {
+    fn symbols(self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+            }
+            Alphabet::UrlSafe => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+            }
+        }
+    }
+
+    fn value(self, symbol: u8) -> Option<u8> {
+        match (self, symbol) {
+            (_, b'A'..=b'Z') => Some(symbol - b'A'),
+            (_, b'a'..=b'z') => Some(symbol - b'a' + 26),
+            (_, b'0'..=b'9') => Some(symbol - b'0' + 52),
+            (Alphabet::Standard, b'+') | (Alphabet::UrlSafe, b'-') => Some(62),
+            (Alphabet::Standard, b'/') | (Alphabet::UrlSafe, b'_') => Some(63),
+            _ => None,
+        }
+    }
+}
+
+/// Decoded bytes, of which only the first `len` are used
+pub(crate) type Group = ([u8; 3], usize);
+
+/// Decodes base64 one symbol at a time.
+///
+/// Padding is optional, but if present it must complete the last group of 4 symbols,
+/// and nothing may follow it.
+#[derive(Debug)]
+pub(crate) struct Decoder {
+    alphabet: Alphabet,
+    // the symbols of the current group, as 6-bit values
+    sextets: [u8; 4],
+    // symbols of the current group, including padding
+    len: usize,
+    padding: usize,
+    // a padded group was completed
+    ended: bool,
+}
+
+impl Decoder {
+    pub(crate) fn new(alphabet: Alphabet) -> Self {
+        Self {
+            alphabet,
+            sextets: [0; 4],
+            len: 0,
+            padding: 0,
+            ended: false,
+        }
+    }
+
+    /// Returns the decoded bytes once a group is complete
+    pub(crate) fn push(&mut self, symbol: u8) -> Result<Option<Group>, SyntaxError> {
+        if self.ended {
+            return Err(SyntaxError::InvalidBase64Padding);
+        }
+        if symbol == b'=' {
+            if self.len < 2 {
+                return Err(SyntaxError::InvalidBase64Padding);
+            }
+            self.padding += 1;
+        } else if self.padding > 0 {
+            return Err(SyntaxError::InvalidBase64Padding);
+        } else {
+            let value = self.alphabet.value(symbol);
+            self.sextets[self.len] = value.ok_or(SyntaxError::InvalidBase64)?;
+        }
+        self.len += 1;
+        if self.len < 4 {
+            return Ok(None);
+        }
+        self.ended = self.padding > 0;
+        Ok(Some(self.take(3 - self.padding)))
+    }
+
+    /// Returns the bytes of a last group without padding
+    pub(crate) fn finish(&mut self) -> Result<Option<Group>, SyntaxError> {
+        match (self.len, self.padding) {
+            (0, _) => Ok(None),
+            (2 | 3, 0) => Ok(Some(self.take(self.len - 1))),
+            _ => Err(SyntaxError::InvalidBase64Padding),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Group {
+        let [a, b, c, d] = self.sextets;
+        self.sextets = [0; 4];
+        self.len = 0;
+        ([a << 2 | b >> 4, b << 4 | c >> 2, c << 6 | d], len)
+    }
+}
+
+/// Encodes bytes as padded base64, 3 bytes at a time
+#[derive(Debug)]
+pub(crate) struct Encoder {
+    alphabet: Alphabet,
+    bytes: [u8; 3],
+    len: usize,
+}
+
+impl Encoder {
+    pub(crate) fn new(alphabet: Alphabet) -> Self {
+        Self {
+            alphabet,
+            bytes: [0; 3],
+            len: 0,
+        }
+    }
+
+    /// Returns the encoded symbols once 3 bytes were pushed
+    pub(crate) fn push(&mut self, b: u8) -> Option<[u8; 4]> {
+        self.bytes[self.len] = b;
+        self.len += 1;
+        if self.len < 3 {
+            return None;
+        }
+        Some(self.take())
+    }
+
+    /// Returns the padded symbols of the last bytes, if any
+    pub(crate) fn finish(&mut self) -> Option<[u8; 4]> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.len;
+        let mut symbols = self.take();
+        symbols[len + 1..].fill(b'=');
+        Some(symbols)
+    }
+
+    fn take(&mut self) -> [u8; 4] {
+        let [a, b, c] = self.bytes;
+        let symbols = self.alphabet.symbols();
+        self.bytes = [0; 3];
+        self.len = 0;
+        [
+            a >> 2,
+            (a << 4 | b >> 4) & 0x3f,
+            (b << 2 | c >> 6) & 0x3f,
+            c & 0x3f,
+        ]
+        .map(|i| symbols[i as usize])
+    }
+}
@@ -1,9 +1,76 @@
+use crate::base64::{Alphabet, Encoder};
+use crate::parse::{Number, RawNumber};
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::fmt;
 use std::io::{self, Write};
 
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "async")]
+pub use nonblocking::*;
+
+/// Settings that change how an emitter writes values.
+/// Passed to [`Emitter::with_options`], and seen by all of its subemitters.
+#[derive(Debug, Clone, Default)]
+pub struct EmitterOptions {
+    non_finite: NonFinite,
+    separator: Separator,
+}
+
+impl EmitterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how `NaN` and infinite floats are written, defaults to [`NonFinite::Error`].
+    pub fn non_finite(mut self, policy: NonFinite) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Sets how top-level values are separated, defaults to [`Separator::Newline`].
+    pub fn separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
+    }
+}
+
+/// How an emitter separates the top-level values it writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Separator {
+    /// A newline between values.
+    #[default]
+    Newline,
+    /// An RFC 7464 JSON text sequence (`application/json-seq`):
+    /// each value is preceded by an ASCII record separator (0x1E), and followed by a newline.
+    ///
+    /// The newline is written right after the value, along with the closing byte of an array,
+    /// object or string that was emitted in parts, so every record is complete without a `flush`.
+    RecordSeparator,
+}
+
+/// The ASCII record separator that starts each value with [`Separator::RecordSeparator`]
+const RS: u8 = 0x1e;
+
+/// What to do with a float that is `NaN` or infinite, since JSON cannot represent them.
+/// This applies to `f32`, `f64` and float [`Number`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Nothing is written for the value, and an error is returned.
+    #[default]
+    Error,
+    /// Writes `null` instead, like JavaScript's `JSON.stringify`.
+    Null,
+    /// Writes `NaN`, `Infinity` or `-Infinity`, like Python's `json` module.
+    /// These are not valid JSON, but are accepted by parsers with
+    /// [`ParserOptions::allow_nan_and_infinity`](crate::parse::ParserOptions::allow_nan_and_infinity).
+    Literal,
+}
+
 pub struct Emitter<W: Write> {
     dst: W,
     started: bool,
+    options: EmitterOptions,
 }
 
 impl<W: Write> Emitter<W> {
@@ -14,61 +81,85 @@ impl<W: Write> Emitter<W> {
         Self {
             dst,
             started: false,
+            options: EmitterOptions::default(),
         }
     }
 
+    /// Replaces the default options
+    pub fn with_options(mut self, options: EmitterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result {
+        self.dst.flush().map_err(Error::from)
+    }
+
     #[inline]
     fn start(&mut self) -> Result {
-        if !self.started {
-            self.started = true;
-            Ok(())
-        } else {
-            self.put(b'\n')
+        if self.options.separator == Separator::RecordSeparator {
+            return self.put(RS);
         }
+        if std::mem::replace(&mut self.started, true) {
+            self.put(b'\n')?;
+        }
+        Ok(())
+    }
+}
+
+/// Ends a top-level value. Each record of a JSON text sequence ends with a newline.
+fn end_record(emit: &mut dyn EmitData) -> Result {
+    if emit.options().separator == Separator::RecordSeparator {
+        emit.put(b'\n')?;
     }
+    Ok(())
 }
 
 impl<W: Write> Emit for Emitter<W> {
     fn emit<T: JsonEmit + ?Sized>(&mut self, value: &T) -> Result {
+        value.check(&self.options)?;
         self.start()?;
-        value.write_to(self)
+        value.write_to(self)?;
+        end_record(self)
     }
 
-    fn string(&mut self) -> Result<EmitString> {
+    fn string(&mut self) -> Result<EmitString<'_>> {
         self.start()?;
-        EmitString::new(self)
+        EmitString::new(self, true)
     }
 
-    fn array(&mut self) -> Result<EmitArray> {
+    fn array(&mut self) -> Result<EmitArray<'_>> {
         self.start()?;
-        EmitArray::new(self)
+        EmitArray::new(self, true)
     }
 
-    fn object(&mut self) -> Result<EmitObject> {
+    fn object(&mut self) -> Result<EmitObject<'_>> {
         self.start()?;
-        EmitObject::new(self)
+        EmitObject::new(self, true)
     }
 }
 
 impl<'a> Emit for EmitArray<'a> {
     fn emit<T: JsonEmit + ?Sized>(&mut self, value: &T) -> Result {
+        value.check(self.emit.options())?;
         self.start()?;
-        value.write_to(self.emit).map_err(Error::from)
+        value.write_to(self.emit)
     }
 
-    fn string(&mut self) -> Result<EmitString> {
+    fn string(&mut self) -> Result<EmitString<'_>> {
         self.start()?;
-        EmitString::new(self.emit)
+        EmitString::new(self.emit, false)
     }
 
-    fn array(&mut self) -> Result<EmitArray> {
+    fn array(&mut self) -> Result<EmitArray<'_>> {
         self.start()?;
-        EmitArray::new(self.emit)
+        EmitArray::new(self.emit, false)
     }
 
-    fn object(&mut self) -> Result<EmitObject> {
+    fn object(&mut self) -> Result<EmitObject<'_>> {
         self.start()?;
-        EmitObject::new(self.emit)
+        EmitObject::new(self.emit, false)
     }
 }
 
@@ -77,17 +168,18 @@ impl<'a> Emit for EmitArray<'a> {
 pub trait Emit {
     fn emit<T: JsonEmit + ?Sized>(&mut self, value: &T) -> Result;
 
-    fn string(&mut self) -> Result<EmitString>;
+    fn string(&mut self) -> Result<EmitString<'_>>;
 
-    fn array(&mut self) -> Result<EmitArray>;
+    fn array(&mut self) -> Result<EmitArray<'_>>;
 
-    fn object(&mut self) -> Result<EmitObject>;
+    fn object(&mut self) -> Result<EmitObject<'_>>;
 }
 
 #[doc(hidden)]
 pub trait EmitData {
     fn put(&mut self, b: u8) -> Result;
     fn write(&mut self) -> &mut dyn Write;
+    fn options(&self) -> &EmitterOptions;
 }
 
 impl<W: Write> EmitData for Emitter<W> {
@@ -97,6 +189,9 @@ impl<W: Write> EmitData for Emitter<W> {
     fn write(&mut self) -> &mut dyn Write {
         self.dst.by_ref()
     }
+    fn options(&self) -> &EmitterOptions {
+        &self.options
+    }
 }
 
 macro_rules! emit_to {
@@ -107,12 +202,14 @@ macro_rules! emit_to {
 
 pub struct EmitString<'a> {
     emit: &'a mut dyn EmitData,
+    // a top-level value, which ends its record
+    root: bool,
 }
 
 impl<'a> EmitString<'a> {
-    fn new(emit: &'a mut dyn EmitData) -> Result<Self> {
+    fn new(emit: &'a mut dyn EmitData, root: bool) -> Result<Self> {
         emit.put(b'"')?;
-        Ok(Self { emit })
+        Ok(Self { emit, root })
     }
 
     pub fn char(&mut self, c: char) -> Result {
@@ -122,25 +219,86 @@ impl<'a> EmitString<'a> {
     pub fn str(&mut self, s: &str) -> Result {
         emit_to!(self.emit.write(), "{}", s)
     }
+
+    /// Returns an [`io::Write`] that base64-encodes the bytes written to it into this string.
+    /// The padding is written by [`EmitBase64::finish`], or when it is dropped.
+    pub fn base64(&mut self, alphabet: Alphabet) -> EmitBase64<'_> {
+        EmitBase64 {
+            emit: self.emit,
+            encoder: Encoder::new(alphabet),
+        }
+    }
+}
+
+/// Base64-encodes bytes into a string, see [`EmitString::base64`].
+pub struct EmitBase64<'a> {
+    emit: &'a mut dyn EmitData,
+    encoder: Encoder,
+}
+
+impl EmitBase64<'_> {
+    /// Writes the padding of the last bytes.
+    /// Unlike dropping this, it returns any error from writing it.
+    pub fn finish(mut self) -> Result {
+        match self.encoder.finish() {
+            Some(symbols) => self.emit.write().write_all(&symbols).map_err(Error::from),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for EmitBase64<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut symbols = [0; 1024];
+        for chunk in buf.chunks(symbols.len() / 4 * 3) {
+            let mut len = 0;
+            for &b in chunk {
+                if let Some(group) = self.encoder.push(b) {
+                    symbols[len..len + 4].copy_from_slice(&group);
+                    len += 4;
+                }
+            }
+            self.emit.write().write_all(&symbols[..len])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.emit.write().flush()
+    }
+}
+
+impl Drop for EmitBase64<'_> {
+    fn drop(&mut self) {
+        if let Some(symbols) = self.encoder.finish() {
+            self.emit.write().write_all(&symbols).unwrap();
+        }
+    }
 }
 
 impl Drop for EmitString<'_> {
     fn drop(&mut self) {
         self.emit.put(b'"').unwrap();
+        if self.root {
+            end_record(self.emit).unwrap();
+        }
     }
 }
 
 pub struct EmitArray<'a> {
     emit: &'a mut dyn EmitData,
     started: bool,
+    // a top-level value, which ends its record
+    root: bool,
 }
 
 impl<'a> EmitArray<'a> {
-    fn new(emit: &'a mut dyn EmitData) -> Result<Self> {
+    fn new(emit: &'a mut dyn EmitData, root: bool) -> Result<Self> {
         emit.put(b'[')?;
         Ok(Self {
             emit,
             started: false,
+            root,
         })
     }
 
@@ -158,20 +316,26 @@ impl<'a> EmitArray<'a> {
 impl Drop for EmitArray<'_> {
     fn drop(&mut self) {
         self.emit.put(b']').unwrap();
+        if self.root {
+            end_record(self.emit).unwrap();
+        }
     }
 }
 
 pub struct EmitObject<'a> {
     emit: &'a mut dyn EmitData,
     started: bool,
+    // a top-level value, which ends its record
+    root: bool,
 }
 
 impl<'a> EmitObject<'a> {
-    fn new(emit: &'a mut dyn EmitData) -> Result<Self> {
+    fn new(emit: &'a mut dyn EmitData, root: bool) -> Result<Self> {
         emit.put(b'{')?;
         Ok(Self {
             emit,
             started: false,
+            root,
         })
     }
 
@@ -200,30 +364,34 @@ impl<'a> EmitObject<'a> {
         S: AsRef<str>,
         V: JsonEmit + ?Sized,
     {
+        value.check(self.emit.options())?;
         self.emit_key(key)?;
         value.write_to(self.emit)
     }
 
-    pub fn emit_array<S>(&mut self, key: S) -> Result<EmitArray>
+    pub fn emit_array<S>(&mut self, key: S) -> Result<EmitArray<'_>>
     where
         S: AsRef<str>,
     {
         self.emit_key(key)?;
-        EmitArray::new(self.emit)
+        EmitArray::new(self.emit, false)
     }
 
-    pub fn emit_object<S>(&mut self, key: S) -> Result<EmitObject>
+    pub fn emit_object<S>(&mut self, key: S) -> Result<EmitObject<'_>>
     where
         S: AsRef<str>,
     {
         self.emit_key(key)?;
-        EmitObject::new(self.emit)
+        EmitObject::new(self.emit, false)
     }
 }
 
 impl Drop for EmitObject<'_> {
     fn drop(&mut self) {
         self.emit.put(b'}').unwrap();
+        if self.root {
+            end_record(self.emit).unwrap();
+        }
     }
 }
 
@@ -235,6 +403,13 @@ mod private {
 pub trait JsonEmit: private::Sealed {
     #[doc(hidden)]
     fn write_to(&self, emit: &mut dyn EmitData) -> Result;
+
+    /// Returns the error that writing this value would fail with, before anything is written.
+    /// This keeps a rejected value from leaving a dangling `,` or key behind.
+    #[doc(hidden)]
+    fn check(&self, _options: &EmitterOptions) -> Result {
+        Ok(())
+    }
 }
 
 macro_rules! impl_json_emit_via_string_format {
@@ -251,9 +426,70 @@ macro_rules! impl_json_emit_via_string_format {
 }
 
 impl_json_emit_via_string_format!(
-    usize, isize, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64, char, bool
+    usize, isize, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, char, bool
 );
 
+// a RawNumber is written exactly as it was read
+impl_json_emit_via_string_format!(RawNumber);
+
+macro_rules! impl_json_emit_for_float {
+    ( $($ty:ty),* ) => {
+        $(
+            impl private::Sealed for $ty {}
+            impl JsonEmit for $ty {
+                fn write_to(&self, emit: &mut dyn EmitData) -> Result {
+                    if self.is_finite() {
+                        emit_to!(emit.write(), "{}", self)
+                    } else {
+                        write_non_finite(emit, f64::from(*self))
+                    }
+                }
+
+                fn check(&self, options: &EmitterOptions) -> Result {
+                    check_finite(options, f64::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+impl_json_emit_for_float!(f32, f64);
+
+impl private::Sealed for Number {}
+impl JsonEmit for Number {
+    fn write_to(&self, emit: &mut dyn EmitData) -> Result {
+        let x = self.as_f64();
+        if x.is_finite() {
+            emit_to!(emit.write(), "{}", self)
+        } else {
+            write_non_finite(emit, x)
+        }
+    }
+
+    fn check(&self, options: &EmitterOptions) -> Result {
+        check_finite(options, self.as_f64())
+    }
+}
+
+fn check_finite(options: &EmitterOptions, x: f64) -> Result {
+    if x.is_finite() || options.non_finite != NonFinite::Error {
+        return Ok(());
+    }
+    Err(Error(Box::new(ErrorCode::NonFiniteFloat)))
+}
+
+fn write_non_finite(emit: &mut dyn EmitData, x: f64) -> Result {
+    let text = match emit.options().non_finite {
+        NonFinite::Error => return check_finite(emit.options(), x),
+        NonFinite::Null => "null",
+        NonFinite::Literal if x.is_nan() => "NaN",
+        NonFinite::Literal if x > 0.0 => "Infinity",
+        NonFinite::Literal => "-Infinity",
+    };
+    emit.write().write_all(text.as_bytes())?;
+    Ok(())
+}
+
 impl private::Sealed for str {}
 impl JsonEmit for str {
     fn write_to(&self, emit: &mut dyn EmitData) -> Result {
@@ -280,12 +516,16 @@ macro_rules! impl_json_emit_for_generic_seq {
             T: JsonEmit,
         {
             fn write_to(&self, emit: &mut dyn EmitData) -> Result {
-                let mut a = EmitArray::new(emit)?;
+                let mut a = EmitArray::new(emit, false)?;
                 for val in self {
                     a.emit(val)?;
                 }
                 Ok(())
             }
+
+            fn check(&self, options: &EmitterOptions) -> Result {
+                self.iter().try_for_each(|val| val.check(options))
+            }
         }
     };
 }
@@ -307,6 +547,11 @@ where
     fn write_to(&self, emit: &mut dyn EmitData) -> Result {
         self.as_slice().write_to(emit)
     }
+
+    #[inline(always)]
+    fn check(&self, options: &EmitterOptions) -> Result {
+        self.as_slice().check(options)
+    }
 }
 
 macro_rules! impl_json_emit_for_generic_map {
@@ -323,12 +568,16 @@ macro_rules! impl_json_emit_for_generic_map {
             V: JsonEmit,
         {
             fn write_to(&self, emit: &mut dyn EmitData) -> Result {
-                let mut o = EmitObject::new(emit)?;
+                let mut o = EmitObject::new(emit, false)?;
                 for (k, v) in self {
                     o.emit(k, v)?;
                 }
                 Ok(())
             }
+
+            fn check(&self, options: &EmitterOptions) -> Result {
+                self.values().try_for_each(|v| v.check(options))
+            }
         }
     };
 }
@@ -341,11 +590,43 @@ type Result<T = ()> = std::result::Result<T, Error>;
 #[derive(Debug)]
 pub struct Error(Box<ErrorCode>);
 
+impl Error {
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        matches!(*self.0, ErrorCode::Io(_))
+    }
+
+    /// Returns true if a `NaN` or infinite float was emitted with [`NonFinite::Error`]
+    #[inline]
+    pub fn is_non_finite_float(&self) -> bool {
+        matches!(*self.0, ErrorCode::NonFiniteFloat)
+    }
+}
+
 // Modeled after serde_json
 #[non_exhaustive]
 #[derive(Debug)]
 pub(crate) enum ErrorCode {
     Io(io::Error),
+    NonFiniteFloat,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.0 {
+            ErrorCode::Io(e) => write!(f, "io error: {}", e),
+            ErrorCode::NonFiniteFloat => f.write_str("cannot emit a non-finite float as JSON"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &*self.0 {
+            ErrorCode::Io(e) => Some(e),
+            ErrorCode::NonFiniteFloat => None,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
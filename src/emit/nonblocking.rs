@@ -0,0 +1,293 @@
+//! Async counterparts of [`Emitter`] and its subemitters.
+//!
+//! An [`AsyncEmitter`] writes to anything implementing [`AsyncWrite`].
+//! Since a [`Drop`] impl cannot await, arrays, objects and strings are closed with an explicit `async fn end()`.
+//! If one is dropped without calling `end`, its closing bytes are kept and written
+//! before the next write on the same emitter, or by [`AsyncEmitter::flush`].
+//!
+//! The futures returned here are [`Send`] whenever the writer is.
+
+use super::{Emitter, EmitterOptions, JsonEmit, Result, Separator, RS};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct AsyncEmitter<W> {
+    dst: W,
+    started: bool,
+    pending: Vec<u8>,
+    options: EmitterOptions,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEmitter<W> {
+    /// Constructs a new AsyncEmitter that will write to the provided [AsyncWrite].
+    pub fn new(dst: W) -> Self {
+        Self {
+            dst,
+            started: false,
+            pending: vec![],
+            options: EmitterOptions::default(),
+        }
+    }
+
+    /// Replaces the default options
+    pub fn with_options(mut self, options: EmitterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[inline]
+    async fn start(&mut self) -> Result {
+        let started = std::mem::replace(&mut self.started, true);
+        match (started, self.options.separator) {
+            (false, Separator::Newline) => Ok(()),
+            (true, Separator::Newline) => self.put(b"\n").await,
+            (_, Separator::RecordSeparator) => self.put(&[RS]).await,
+        }
+    }
+
+    pub async fn emit<T: JsonEmit + ?Sized>(&mut self, value: &T) -> Result {
+        let mut buf = self.encode(value)?;
+        buf.extend_from_slice(self.record_end());
+        self.start().await?;
+        self.put(&buf).await
+    }
+
+    pub async fn string(&mut self) -> Result<AsyncEmitString<'_, W>> {
+        self.start().await?;
+        AsyncEmitString::new(self, true).await
+    }
+
+    pub async fn array(&mut self) -> Result<AsyncEmitArray<'_, W>> {
+        self.start().await?;
+        AsyncEmitArray::new(self, true).await
+    }
+
+    pub async fn object(&mut self) -> Result<AsyncEmitObject<'_, W>> {
+        self.start().await?;
+        AsyncEmitObject::new(self, true).await
+    }
+
+    /// Writes the closing bytes of any dropped subemitters, then flushes the underlying writer.
+    pub async fn flush(&mut self) -> Result {
+        self.put(&[]).await?;
+        self.dst.flush().await?;
+        Ok(())
+    }
+
+    /// Writes `bytes`, preceded by the closing bytes of any dropped subemitters.
+    async fn put(&mut self, bytes: &[u8]) -> Result {
+        if !self.pending.is_empty() {
+            self.dst.write_all(&self.pending).await?;
+            self.pending.clear();
+        }
+        self.dst.write_all(bytes).await?;
+        Ok(())
+    }
+
+    /// Writes the closing byte `b` of a subemitter, see `put_later`
+    async fn close(&mut self, b: u8, root: bool) -> Result {
+        self.put_later(b, root);
+        self.put(&[]).await
+    }
+}
+
+impl<W> AsyncEmitter<W> {
+    /// Closing bytes can only be queued, since this is called from [`Drop`].
+    /// A `root` value also ends its record.
+    fn put_later(&mut self, b: u8, root: bool) {
+        self.pending.push(b);
+        if root {
+            self.pending.extend_from_slice(self.record_end());
+        }
+    }
+
+    /// The bytes after a top-level value. Each record of a JSON text sequence ends with a newline.
+    fn record_end(&self) -> &'static [u8] {
+        match self.options.separator {
+            Separator::Newline => b"",
+            Separator::RecordSeparator => b"\n",
+        }
+    }
+
+    /// Values are formatted by their sync [`JsonEmit`] impl, then written all at once.
+    fn encode<T: JsonEmit + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        value.write_to(&mut Emitter::new(&mut buf).with_options(self.options.clone()))?;
+        Ok(buf)
+    }
+}
+
+pub struct AsyncEmitString<'a, W> {
+    emit: &'a mut AsyncEmitter<W>,
+    // a top-level value, which ends its record
+    root: bool,
+    ended: bool,
+}
+
+impl<'a, W: AsyncWrite + Unpin> AsyncEmitString<'a, W> {
+    async fn new(emit: &'a mut AsyncEmitter<W>, root: bool) -> Result<Self> {
+        emit.put(b"\"").await?;
+        Ok(Self {
+            emit,
+            root,
+            ended: false,
+        })
+    }
+
+    pub async fn char(&mut self, c: char) -> Result {
+        self.emit.put(c.encode_utf8(&mut [0; 4]).as_bytes()).await
+    }
+
+    pub async fn str(&mut self, s: &str) -> Result {
+        self.emit.put(s.as_bytes()).await
+    }
+
+    /// Writes the closing quote of this string.
+    pub async fn end(mut self) -> Result {
+        self.ended = true;
+        self.emit.close(b'"', self.root).await
+    }
+}
+
+impl<W> Drop for AsyncEmitString<'_, W> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.emit.put_later(b'"', self.root);
+        }
+    }
+}
+
+pub struct AsyncEmitArray<'a, W> {
+    emit: &'a mut AsyncEmitter<W>,
+    started: bool,
+    // a top-level value, which ends its record
+    root: bool,
+    ended: bool,
+}
+
+impl<'a, W: AsyncWrite + Unpin> AsyncEmitArray<'a, W> {
+    async fn new(emit: &'a mut AsyncEmitter<W>, root: bool) -> Result<Self> {
+        emit.put(b"[").await?;
+        Ok(Self {
+            emit,
+            started: false,
+            root,
+            ended: false,
+        })
+    }
+
+    #[inline]
+    async fn start(&mut self) -> Result {
+        if !self.started {
+            self.started = true;
+            Ok(())
+        } else {
+            self.emit.put(b",").await
+        }
+    }
+
+    pub async fn emit<T: JsonEmit + ?Sized>(&mut self, value: &T) -> Result {
+        let buf = self.emit.encode(value)?;
+        self.start().await?;
+        self.emit.put(&buf).await
+    }
+
+    pub async fn string(&mut self) -> Result<AsyncEmitString<'_, W>> {
+        self.start().await?;
+        AsyncEmitString::new(self.emit, false).await
+    }
+
+    pub async fn array(&mut self) -> Result<AsyncEmitArray<'_, W>> {
+        self.start().await?;
+        AsyncEmitArray::new(self.emit, false).await
+    }
+
+    pub async fn object(&mut self) -> Result<AsyncEmitObject<'_, W>> {
+        self.start().await?;
+        AsyncEmitObject::new(self.emit, false).await
+    }
+
+    /// Writes the closing bracket of this array.
+    pub async fn end(mut self) -> Result {
+        self.ended = true;
+        self.emit.close(b']', self.root).await
+    }
+}
+
+impl<W> Drop for AsyncEmitArray<'_, W> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.emit.put_later(b']', self.root);
+        }
+    }
+}
+
+pub struct AsyncEmitObject<'a, W> {
+    emit: &'a mut AsyncEmitter<W>,
+    started: bool,
+    // a top-level value, which ends its record
+    root: bool,
+    ended: bool,
+}
+
+impl<'a, W: AsyncWrite + Unpin> AsyncEmitObject<'a, W> {
+    async fn new(emit: &'a mut AsyncEmitter<W>, root: bool) -> Result<Self> {
+        emit.put(b"{").await?;
+        Ok(Self {
+            emit,
+            started: false,
+            root,
+            ended: false,
+        })
+    }
+
+    #[inline(always)]
+    async fn emit_key(&mut self, key: &str) -> Result {
+        let mut buf = self.emit.encode(key)?;
+        if self.started {
+            buf.insert(0, b',');
+        }
+        self.started = true;
+        buf.push(b':');
+        self.emit.put(&buf).await
+    }
+
+    pub async fn emit<S, V>(&mut self, key: S, value: &V) -> Result
+    where
+        S: AsRef<str>,
+        V: JsonEmit + ?Sized,
+    {
+        let buf = self.emit.encode(value)?;
+        self.emit_key(key.as_ref()).await?;
+        self.emit.put(&buf).await
+    }
+
+    pub async fn emit_array<S>(&mut self, key: S) -> Result<AsyncEmitArray<'_, W>>
+    where
+        S: AsRef<str>,
+    {
+        self.emit_key(key.as_ref()).await?;
+        AsyncEmitArray::new(self.emit, false).await
+    }
+
+    pub async fn emit_object<S>(&mut self, key: S) -> Result<AsyncEmitObject<'_, W>>
+    where
+        S: AsRef<str>,
+    {
+        self.emit_key(key.as_ref()).await?;
+        AsyncEmitObject::new(self.emit, false).await
+    }
+
+    /// Writes the closing brace of this object.
+    pub async fn end(mut self) -> Result {
+        self.ended = true;
+        self.emit.close(b'}', self.root).await
+    }
+}
+
+impl<W> Drop for AsyncEmitObject<'_, W> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.emit.put_later(b'}', self.root);
+        }
+    }
+}
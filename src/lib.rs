@@ -23,13 +23,19 @@
 //! ## Error recovery
 //!
 //! JSON Stream parsers can perform some error recovery. Errors that are nonfatal are marked as such, and after one is returned parsing may continue.
+//! Use [`Error::is_fatal`](parse::Error::is_fatal) to tell them apart.
+//!
+//! After a nonfatal error inside an array or object, the parser resynchronizes by skipping
+//! the rest of the invalid item, up to the next `,`, `]` or `}` at the same depth.
+//! This makes it possible to salvage the valid records of a partially corrupt document.
 //!
 //! ## Sync and Async
 //!
 //! By default, JSON Stream exposes a sync interface, via the default `sync` feature.
 //! To enable async support, enable the `async` feature.
 //! The async API is similar to the sync one, with the exception of using
-//! AsyncRead and AsyncWrite as the underlying traits for parsing/emitting.
+//! [tokio](https://docs.rs/tokio)'s `AsyncRead` and `AsyncWrite` as the underlying traits for parsing/emitting,
+//! and of methods that read or write data being `async fn`s.
 //!
 //! ## `serde_json` integration
 //!
@@ -39,5 +45,6 @@
 //! Enable the `serde_json` feature to expose `Serialize`/`Deserializer` implementations that allow
 //!
 //!
+pub mod base64;
 pub mod emit;
 pub mod parse;
@@ -1,7 +1,7 @@
 //! # Parse json
 //!
 //! This module provides a way to lazily parse JSON data.
-//! A [`Parser`] reads from anything implementing [`Read`], and will yield
+//! A [`Parser`] reads from anything implementing [`Read`] or [`BufRead`], and will yield
 //! a sequence of [`Json`] values. Fixed-size items are parsed as values directly,
 //! but for strings, arrays and objects, subparsers are returned instead.
 //! The caller can then invoke these subparsers to actually parse the content of that item.
@@ -12,116 +12,665 @@
 //! When a [`ParseString`], [`ParseArray`], [`ParseObject`], or [`KeyVal`] is dropped,
 //! that item, and everything it contains is skipped. Skipping is done efficiently and lazily,
 //! occurring only on the following call to `fn next`, which will return the next Json item
-//! on the same level.
+//! on the same level. If the input ends before the skipped item does, that call returns
+//! [`SyntaxError::EofWhileParsingList`], [`SyntaxError::EofWhileParsingObject`]
+//! or [`SyntaxError::EofWhileParsingString`] instead.
+//!
+//!
+//! ## Records
+//!
+//! [`JsonLines`] reads newline-delimited JSON, one value per line, and [`JsonSeq`] reads
+//! RFC 7464 JSON text sequences. Both keep errors from spilling over into the following records.
 
-use core::convert::TryFrom;
-use std::io::{self, ErrorKind, Read};
-use std::iter::Peekable;
+use crate::base64::{Alphabet, Decoder, Group};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
 use std::ptr;
 
-/// Reads bytes from a [`Read`], parses them as [`Json`], and returns a stream of values or sub-parsers via `fn next()`
-pub struct Parser<R: Read> {
-    src: Peekable<io::Bytes<R>>,
+mod records;
+pub use records::*;
+
+mod scan;
+use scan::{CharDecoder, Decoded, HexScanner, NumStep, NumberScanner, Skipper};
+
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "async")]
+pub use nonblocking::*;
+
+/// Reads bytes from a [`BufRead`], parses them as [`Json`], and returns a stream of values or sub-parsers via `fn next()`
+pub struct Parser<R: BufRead> {
+    src: R,
     skips: Vec<Skip>,
+    // set after an I/O error, from then on the input is treated as ended
+    failed: bool,
+    pos: Position,
+    options: ParserOptions,
+    // arrays and objects that were opened and not yet closed or skipped
+    depth: usize,
+    roots: Roots,
+    // set by `JsonLines` and `JsonSeq`, this byte then looks like the end of the input
+    record_end: Option<u8>,
+}
+
+/// Settings that change how a parser reads its input.
+/// Passed to [`Parser::with_options`], and seen by all of its subparsers.
+///
+/// The defaults only accept JSON as specified by RFC 8259.
+/// Leniencies can be enabled one by one, or all at once with [`ParserOptions::lenient`].
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    number_mode: NumberMode,
+    max_depth: usize,
+    allow_trailing_commas: bool,
+    allow_missing_commas: bool,
+    allow_bare_keys: bool,
+    allow_comments: bool,
+    allow_single_quotes: bool,
+    allow_hex_numbers: bool,
+    allow_relaxed_numbers: bool,
+    allow_nan_and_infinity: bool,
+    allow_js_escapes: bool,
+    allow_control_characters: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            number_mode: NumberMode::default(),
+            max_depth: 128,
+            allow_trailing_commas: false,
+            allow_missing_commas: false,
+            allow_bare_keys: false,
+            allow_comments: false,
+            allow_single_quotes: false,
+            allow_hex_numbers: false,
+            allow_relaxed_numbers: false,
+            allow_nan_and_infinity: false,
+            allow_js_escapes: false,
+            allow_control_characters: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accepts RFC 8259 JSON. This is the same as the defaults.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Accepts trailing commas, missing commas, bare keys and control characters in strings
+    pub fn lenient() -> Self {
+        Self::default()
+            .allow_trailing_commas(true)
+            .allow_missing_commas(true)
+            .allow_bare_keys(true)
+            .allow_control_characters(true)
+    }
+
+    /// Accepts JSON with comments, as used by many config files: comments and trailing commas
+    pub fn jsonc() -> Self {
+        Self::default()
+            .allow_comments(true)
+            .allow_trailing_commas(true)
+    }
+
+    /// Accepts JSON5: comments, trailing commas, bare keys, single-quoted strings with JavaScript escapes,
+    /// hexadecimal integers, numbers like `+1`, `.5` or `5.`, `NaN` and `Infinity`.
+    ///
+    /// Bare keys and whitespace are limited to ASCII, so keys with other Unicode letters must be quoted,
+    /// and Unicode spaces other than the ones of JSON are rejected.
+    pub fn json5() -> Self {
+        Self::jsonc()
+            .allow_bare_keys(true)
+            .allow_single_quotes(true)
+            .allow_js_escapes(true)
+            .allow_hex_numbers(true)
+            .allow_relaxed_numbers(true)
+            .allow_nan_and_infinity(true)
+    }
+
+    /// Accepts a comma after the last item of an array or object, like `[1, 2,]`.
+    /// Otherwise, it is returned as [`SyntaxError::TrailingComma`].
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+
+    /// Accepts array items and object entries that are only separated by whitespace, like `[1 2]`.
+    /// Otherwise, they are returned as [`SyntaxError::MissingComma`].
+    pub fn allow_missing_commas(mut self, allow: bool) -> Self {
+        self.allow_missing_commas = allow;
+        self
+    }
+
+    /// Accepts object keys that are unquoted identifiers, like `{key: 1}`.
+    /// Identifiers are made of ASCII letters, digits, `_` and `$`, and can't start with a digit.
+    pub fn allow_bare_keys(mut self, allow: bool) -> Self {
+        self.allow_bare_keys = allow;
+        self
+    }
+
+    /// Accepts `// line` and `/* block */` comments wherever whitespace is allowed.
+    /// A `/` that doesn't start a comment is returned as [`SyntaxError::InvalidComment`].
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Accepts strings and keys in single quotes, like `'it\'s'`.
+    /// The escape `\'` is then accepted in all strings.
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    /// Accepts hexadecimal integers, like `0x1F` or `-0xff`.
+    /// With [`NumberMode::Raw`], they are returned as decimal text.
+    pub fn allow_hex_numbers(mut self, allow: bool) -> Self {
+        self.allow_hex_numbers = allow;
+        self
+    }
+
+    /// Accepts numbers with a leading `+`, and a decimal point without digits before or after it,
+    /// like `+1`, `.5` or `5.`. With [`NumberMode::Raw`], they are returned as valid JSON, like `0.5` or `5.0`.
+    pub fn allow_relaxed_numbers(mut self, allow: bool) -> Self {
+        self.allow_relaxed_numbers = allow;
+        self
+    }
+
+    /// Accepts the literals `NaN` and `Infinity`, as floats, optionally after a `-`,
+    /// or a `+` with [`allow_relaxed_numbers`](Self::allow_relaxed_numbers).
+    /// They are returned as [`Json::Number`] even with [`NumberMode::Raw`],
+    /// since a [`RawNumber`] only holds valid JSON numbers.
+    pub fn allow_nan_and_infinity(mut self, allow: bool) -> Self {
+        self.allow_nan_and_infinity = allow;
+        self
+    }
+
+    /// Accepts the string escapes JavaScript adds to JSON: `\x41`, `\v`, `\0`,
+    /// and any other escaped char standing for itself, like `\a`.
+    /// An escaped line break is removed, so that a long string can go on over several lines.
+    /// Otherwise, they are returned as [`SyntaxError::InvalidEscape`].
+    pub fn allow_js_escapes(mut self, allow: bool) -> Self {
+        self.allow_js_escapes = allow;
+        self
+    }
+
+    /// Accepts unescaped control characters (U+0000 to U+001F), like tabs and newlines, inside strings.
+    /// Otherwise, they are returned as [`SyntaxError::ControlCharacterWhileParsingString`],
+    /// and the rest of the string is skipped.
+    pub fn allow_control_characters(mut self, allow: bool) -> Self {
+        self.allow_control_characters = allow;
+        self
+    }
+
+    /// Sets how many arrays and objects may be open at once, defaults to 128.
+    /// Going deeper returns [`SyntaxError::RecursionLimitExceeded`], and the value that was too deep is skipped.
+    ///
+    /// Skipping never recurses, so dropped values are skipped regardless of their depth.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets how numbers are returned, defaults to [`NumberMode::Number`]
+    pub fn number_mode(mut self, mode: NumberMode) -> Self {
+        self.number_mode = mode;
+        self
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    /// Numbers are converted and returned as [`Json::Number`]
+    #[default]
+    Number,
+    /// Numbers are returned as [`Json::RawNumber`], keeping their exact text
+    Raw,
 }
 
 type Result<'a, T = Json<'a>> = std::result::Result<T, Error>;
 
-impl<R: Read> Parser<R> {
+impl<R: Read> Parser<BufReader<R>> {
     /// Constructs a new Parser that will read from the provided object.
+    /// Reads are buffered internally, so `r` does not need to be buffered.
     pub fn new(r: R) -> Self {
+        Self::from_buf_read(BufReader::new(r))
+    }
+}
+
+impl<'s> Parser<&'s [u8]> {
+    /// Constructs a new Parser over input that is already in memory.
+    /// Strings without escapes can then be read without copying, via [`ParseString::read_cow`].
+    pub fn from_slice(s: &'s [u8]) -> Self {
+        Self::from_buf_read(s)
+    }
+
+    /// Same as [`Parser::from_slice`], for a `&str`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'s str) -> Self {
+        Self::from_slice(s.as_bytes())
+    }
+}
+
+impl<R: BufRead> Parser<R> {
+    /// Constructs a new Parser that will read from the provided object,
+    /// using its buffer directly.
+    pub fn from_buf_read(r: R) -> Self {
         Self {
-            src: r.bytes().peekable(),
+            src: r,
             skips: vec![],
+            failed: false,
+            pos: Position::default(),
+            options: ParserOptions::default(),
+            depth: 0,
+            roots: Roots::new(StreamMode::default()),
+            record_end: None,
         }
     }
 
+    /// Replaces the default options
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Only accepts a single JSON document, instead of a stream of values.
+    ///
+    /// The first call to `next` returns the root value, or [`SyntaxError::EofWhileParsingValue`] if there is none.
+    /// Once it was read or dropped, the next call returns [`SyntaxError::TrailingCharacters`]
+    /// if anything but whitespace follows, and the parser ends there.
+    ///
+    /// This is the same as `stream_mode(StreamMode::Single)`.
+    pub fn single(self) -> Self {
+        self.stream_mode(StreamMode::Single)
+    }
+
+    /// Sets how top-level values are expected to be separated, defaults to [`StreamMode::Concatenated`].
+    pub fn stream_mode(mut self, mode: StreamMode) -> Self {
+        self.roots = Roots::new(mode);
+        self
+    }
+
+    /// Returns the position of the next byte that will be read.
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
     /// Returns the next JSON item.
-    /// A Parser will read any number of whitespace-separated JSON items and return them in order.
+    /// A Parser will read any number of JSON items and return them in order,
+    /// separated as its [`StreamMode`] requires, or only one with `single`.
     /// Returns None when the input is exhausted.
-    pub fn next(&mut self) -> Option<Result> {
-        self.do_skips();
-        self.eat_whitespace();
-        Some(next_any_item(self.next_byte()?, self))
+    ///
+    /// If reading fails, the I/O error is returned, and the parser
+    /// (including any live subparsers) behaves as if the input ended there.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<'_>> {
+        if let Err(e) = self.do_skips() {
+            return Some(Err(e));
+        }
+        let end_of_last = self.pos;
+        if let Err(e) = (self as &mut dyn Parse).eat_whitespace() {
+            return Some(Err(e));
+        }
+        let b = match self.peek_byte() {
+            Ok(b) => b,
+            Err(e) => return Some(Err(e)),
+        };
+        match self.roots.next(b, end_of_last, self.pos)? {
+            Ok(()) => {
+                self.consume_byte();
+                Some(next_any_item(b?, self))
+            }
+            Err(code) => Some(Err(Error::new(ErrorCode::Syntax(code), Some(self.pos)))),
+        }
+    }
+}
+
+/// How a parser expects its top-level values to be separated, see [`Parser::stream_mode`].
+///
+/// Values that are not separated as required are reported once as [`SyntaxError::MissingSeparator`],
+/// and then returned by the next call to `next`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum StreamMode {
+    /// Values may follow each other directly, like `{"a":1}{"b":2}[3]`.
+    /// Only a number, `true`, `false` or `null` needs whitespace before another one of these,
+    /// since `1 2` would otherwise read as `12`.
+    #[default]
+    Concatenated,
+    /// Values are separated by whitespace.
+    Whitespace,
+    /// Each value is on a line of its own.
+    /// To keep an error from affecting the values after it, see [`JsonLines`].
+    NewlineDelimited,
+    /// Only a single value, see [`Parser::single`].
+    Single,
+}
+
+/// Which top-level values a parser still accepts, according to its stream mode
+#[derive(Debug, Copy, Clone)]
+struct Roots {
+    mode: StreamMode,
+    state: RootState,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RootState {
+    /// No value was returned yet
+    Start,
+    /// A value was returned, and it was a number or literal if `scalar`
+    After { scalar: bool },
+    /// The next value was not separated from the last one, which was already reported
+    Reported,
+    /// Nothing, after a single value was followed by anything else
+    Done,
+}
+
+impl Roots {
+    fn new(mode: StreamMode) -> Self {
+        Self {
+            mode,
+            state: RootState::Start,
+        }
+    }
+
+    /// Given the next byte after whitespace, and the positions before and after that whitespace,
+    /// decides whether `next` returns a value (`Ok`), an error, or None
+    fn next(
+        &mut self,
+        b: Option<u8>,
+        before: Position,
+        after: Position,
+    ) -> Option<std::result::Result<(), SyntaxError>> {
+        let Some(b) = b else {
+            if (self.mode, self.state) == (StreamMode::Single, RootState::Start) {
+                self.state = RootState::Done;
+                return Some(Err(SyntaxError::EofWhileParsingValue));
+            }
+            return None;
+        };
+        let separated = match (self.mode, self.state) {
+            (_, RootState::Done) => return None,
+            (_, RootState::Start | RootState::Reported) => true,
+            (StreamMode::Single, RootState::After { .. }) => {
+                self.state = RootState::Done;
+                return Some(Err(SyntaxError::TrailingCharacters));
+            }
+            (StreamMode::Concatenated, RootState::After { scalar }) => {
+                !(scalar && starts_scalar(b)) || after.offset > before.offset
+            }
+            (StreamMode::Whitespace, RootState::After { .. }) => after.offset > before.offset,
+            (StreamMode::NewlineDelimited, RootState::After { .. }) => after.line > before.line,
+        };
+        if !separated {
+            self.state = RootState::Reported;
+            return Some(Err(SyntaxError::MissingSeparator));
+        }
+        self.state = RootState::After {
+            scalar: starts_scalar(b),
+        };
+        Some(Ok(()))
     }
 }
 
+/// Returns true if a value starting with `b` is a number or literal, rather than a string, array or object
+fn starts_scalar(b: u8) -> bool {
+    !matches!(b, b'"' | b'\'' | b'[' | b'{')
+}
+
 /// This trait exists to allow `ParseArray` and `ParseObject` to
-/// not depend on the original `R: Read` from the base `Parser`
+/// not depend on the original `R: BufRead` from the base `Parser`
 trait Parse {
-    fn next_byte(&mut self) -> Option<u8>;
-    fn peek_byte(&mut self) -> Option<u8>;
-    fn eat_until_whitespace(&mut self);
-    fn eat_whitespace(&mut self);
+    /// Returns the buffered input, reading more if the buffer is empty.
+    /// An empty slice means the input has ended.
+    fn fill_buf(&mut self) -> Result<'static, &[u8]>;
+    /// Advances past `n` bytes of the slice returned by `fill_buf`
+    fn consume(&mut self, n: usize);
+    fn position(&self) -> Position;
+    fn options(&self) -> &ParserOptions;
+    fn depth(&mut self) -> &mut usize;
+    /// Returns true once reading from the source failed, after which the input looks ended
+    fn failed(&self) -> bool;
     fn add_skip(&mut self, s: Skip);
-    fn do_skips(&mut self);
+    fn do_skips(&mut self) -> Result<'static, ()>;
+
+    fn peek_byte(&mut self) -> Result<'static, Option<u8>> {
+        Ok(self.fill_buf()?.first().copied())
+    }
+
+    /// Advances past a byte that was returned by `peek_byte`
+    fn consume_byte(&mut self) {
+        self.consume(1);
+    }
+}
+
+impl dyn Parse + '_ {
+    /// Consumes whitespace, and comments if they are allowed
+    fn eat_whitespace(&mut self) -> Result<'static, ()> {
+        loop {
+            self.scan_while(|b| b.is_ascii_whitespace(), |_| ())?;
+            if !(self.options().allow_comments && self.peek_byte()? == Some(b'/')) {
+                return Ok(());
+            }
+            self.consume_byte();
+            match self.peek_byte()? {
+                Some(kind @ (b'/' | b'*')) => {
+                    self.consume_byte();
+                    self.skip(Skipper::comment(kind, self.options()))?;
+                }
+                _ => return Err(self.invalid(SyntaxError::InvalidComment)),
+            }
+        }
+    }
+
+    /// Builds an error located at the current position
+    fn error(&self, code: SyntaxError) -> Error {
+        Error::new(ErrorCode::Syntax(code), Some(self.position()))
+    }
+
+    /// Builds an error for malformed input located at the current position.
+    /// The rest of the invalid item is skipped by the next call to `next`, see `skip_entry`.
+    fn invalid(&mut self, code: SyntaxError) -> Error {
+        let e = self.error(code);
+        self.add_skip(Skip::Entry);
+        e
+    }
+
+    /// Skips the rest of a string after `e` was returned while reading it.
+    /// If the input ended inside the string, that was already reported by `e`.
+    fn skip_string_after(&mut self, delim: Delim, e: &Error) {
+        if e.syntax() != Some(SyntaxError::EofWhileParsingString) {
+            self.add_skip(Skip::String(delim));
+        }
+    }
+
+    /// Called after the opening bracket of an array or object was consumed.
+    /// If that is too deep, the value is skipped instead, using `skip`.
+    fn enter(&mut self, skip: Skip) -> Result<'static, ()> {
+        let e = self.error(SyntaxError::RecursionLimitExceeded);
+        let max = self.options().max_depth;
+        let depth = self.depth();
+        *depth += 1;
+        if *depth > max {
+            // the skip closes this level again
+            self.add_skip(skip);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Called after the closing bracket of an array or object was consumed
+    fn leave(&mut self) {
+        let depth = self.depth();
+        *depth = depth.saturating_sub(1);
+    }
+
+    /// Consumes bytes while `pred` holds, passing them to `f` one buffered run at a time
+    fn scan_while(
+        &mut self,
+        pred: impl Fn(u8) -> bool,
+        mut f: impl FnMut(&[u8]),
+    ) -> Result<'static, ()> {
+        loop {
+            let buf = self.fill_buf()?;
+            let n = buf.iter().take_while(|b| pred(**b)).count();
+            f(&buf[..n]);
+            let more = n > 0 && n == buf.len();
+            self.consume(n);
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consumes input until `skipper` is done
+    fn skip(&mut self, mut skipper: Skipper) -> Result<'static, ()> {
+        loop {
+            let (n, done) = skipper.scan(self.fill_buf()?);
+            self.consume(n);
+            if let Some(done) = done {
+                return done.map_err(|e| self.error(e));
+            }
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
 enum Skip {
     Array,
     Object,
-    ObjectValue { key_consumed: bool },
-    String,
+    /// The value of an object entry, after its key was read or skipped
+    ObjectValue,
+    String(Delim),
+    /// Bytes still in the buffer, because they were lent out
+    Bytes(usize),
+    /// The rest of an array item or object entry, up to the next `,`, `]` or `}` at the same depth
+    Entry,
+}
+
+/// How the end of a string is found
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Delim {
+    /// Ends at this unescaped quote. The opening one was already consumed.
+    Quote(u8),
+    /// An unquoted key, which ends before the first byte that can't be part of an identifier
+    Bare,
+}
+
+const DOUBLE_QUOTE: Delim = Delim::Quote(b'"');
+const SINGLE_QUOTE: Delim = Delim::Quote(b'\'');
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+fn is_ident_start(b: u8) -> bool {
+    is_ident_byte(b) && !b.is_ascii_digit()
 }
 
-impl<R: Read> Parse for Parser<R> {
-    fn next_byte(&mut self) -> Option<u8> {
-        match self.src.next()? {
-            Ok(b) => Some(b),
-            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
-            Err(e) => panic!("error reading: {:?}", e),
+/// Returns true for the bytes of numbers, `true`, `false`, `null` and the non-finite numbers
+fn is_scalar_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'.' | b'+' | b'-')
+}
+
+impl<R: BufRead> Parse for Parser<R> {
+    fn fill_buf(&mut self) -> Result<'static, &[u8]> {
+        if self.failed {
+            return Ok(&[]);
+        }
+        loop {
+            match self.src.fill_buf() {
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(&[]),
+                Err(e) => {
+                    self.failed = true;
+                    self.skips.clear();
+                    return Err(Error::new(ErrorCode::Io(e), Some(self.pos)));
+                }
+            }
         }
+        // returning the buffer from inside the loop doesn't pass the borrow checker,
+        // but a second call only returns the already filled buffer
+        let buf = self.src.fill_buf().unwrap_or_default();
+        if let Some(delim) = self.record_end {
+            if let Some(end) = buf.iter().position(|b| *b == delim) {
+                return Ok(&buf[..end]);
+            }
+        }
+        Ok(buf)
     }
 
-    fn peek_byte(&mut self) -> Option<u8> {
-        match self.src.peek()? {
-            Ok(b) => Some(*b),
-            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
-            Err(e) => panic!("error reading: {:?}", e),
+    fn consume(&mut self, n: usize) {
+        if n == 0 {
+            return;
         }
+        if let Ok(buf) = self.src.fill_buf() {
+            self.pos.advance_by(&buf[..n]);
+        }
+        self.src.consume(n);
     }
 
-    fn eat_until_whitespace(&mut self) {
-        while let Some(b) = self.next_byte() {
-            if b.is_ascii_whitespace() {
-                break;
-            }
-        }
+    fn position(&self) -> Position {
+        self.pos
     }
 
-    fn eat_whitespace(&mut self) {
-        while let Some(b) = self.peek_byte() {
-            if !b.is_ascii_whitespace() {
-                break;
-            }
-            self.next_byte();
-        }
+    fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self.depth
+    }
+
+    fn failed(&self) -> bool {
+        self.failed
     }
 
     fn add_skip(&mut self, s: Skip) {
-        self.skips.push(s);
+        if !self.failed {
+            self.skips.push(s);
+        }
     }
 
-    fn do_skips(&mut self) {
+    fn do_skips(&mut self) -> Result<'static, ()> {
         if self.skips.is_empty() {
-            return;
+            return Ok(());
         }
         let skips = std::mem::take(&mut self.skips);
+        let parse: &mut dyn Parse = self;
         for skip in skips {
             match skip {
-                Skip::String => skip_string(self),
-                Skip::Array => skip_array(self),
-                Skip::Object => skip_obj(self),
-                Skip::ObjectValue { key_consumed } => skip_obj_value(self, key_consumed),
+                Skip::String(delim) => parse.skip(Skipper::string(delim, parse.options()))?,
+                Skip::Array => {
+                    let skipped = parse.skip(Skipper::nested(b'[', parse.options()));
+                    parse.leave();
+                    skipped?;
+                }
+                Skip::Object => {
+                    let skipped = parse.skip(Skipper::nested(b'{', parse.options()));
+                    parse.leave();
+                    skipped?;
+                }
+                Skip::ObjectValue => skip_obj_value(parse)?,
+                Skip::Bytes(n) => parse.consume(n),
+                Skip::Entry => skip_entry(parse)?,
             }
         }
+        Ok(())
     }
 }
 
 fn next_any_item<'a>(b: u8, parse: &'a mut (dyn Parse + 'a)) -> Result<'a> {
     match b {
         b'0'..=b'9' | b'-' => parse_number(parse, b),
+        b'.' | b'+' if parse.options().allow_relaxed_numbers => parse_number(parse, b),
         b'n' => {
             must_eat_ident(parse, b"ull")?;
             Ok(Json::Null)
@@ -134,73 +683,158 @@ fn next_any_item<'a>(b: u8, parse: &'a mut (dyn Parse + 'a)) -> Result<'a> {
             must_eat_ident(parse, b"alse")?;
             Ok(Json::Bool(false))
         }
-        b'[' => Ok(Json::Array(ParseArray::new(parse))),
-        b'{' => Ok(Json::Object(ParseObject::new(parse))),
-        b'"' => Ok(Json::String(ParseString::new(parse))),
-        b if b.is_ascii_alphabetic() => {
-            parse.eat_until_whitespace();
-            Err(SyntaxError::InvalidIdentifier.into())
+        b'[' => {
+            parse.enter(Skip::Array)?;
+            Ok(Json::Array(ParseArray::new(parse)))
+        }
+        b'{' => {
+            parse.enter(Skip::Object)?;
+            Ok(Json::Object(ParseObject::new(parse)))
         }
-        other => panic!("unhandled {:?}", char::from(other)),
+        b'"' => Ok(Json::String(ParseString::new(parse, DOUBLE_QUOTE))),
+        b'\'' if parse.options().allow_single_quotes => {
+            Ok(Json::String(ParseString::new(parse, SINGLE_QUOTE)))
+        }
+        b'I' | b'N' if parse.options().allow_nan_and_infinity => parse_non_finite(parse, b, false),
+        b if b.is_ascii_alphabetic() => Err(parse.invalid(SyntaxError::InvalidIdentifier)),
+        b'.' | b'+' => Err(parse.invalid(SyntaxError::InvalidNumber)),
+        _ => Err(parse.invalid(SyntaxError::ExpectedSomeValue)),
     }
 }
 
 fn must_eat_ident<'a>(parse: &mut dyn Parse, ident: &[u8]) -> Result<'a, ()> {
     for b in ident {
-        let read = match parse.next_byte() {
+        let read = match parse.peek_byte()? {
             Some(b) => b,
-            _ => return Err(SyntaxError::EofWhileParsingValue.into()),
+            _ => return Err(parse.error(SyntaxError::EofWhileParsingValue)),
         };
         if *b != read {
-            parse.eat_until_whitespace();
-            return Err(SyntaxError::InvalidIdentifier.into());
+            // a delimiter right after the identifier is left in place
+            if read.is_ascii_alphanumeric() {
+                parse.consume_byte();
+            }
+            return Err(parse.invalid(SyntaxError::InvalidIdentifier));
         }
+        parse.consume_byte();
     }
     Ok(())
 }
 
-fn parse_number(parse: &mut dyn Parse, byte: u8) -> Result {
-    let mut s = String::new();
-    s.push(byte.into());
-    while let Some(b) = parse.peek_byte() {
-        match b {
-            b'0'..=b'9' | b'.' | b'e' | b'+' | b'-' => {
-                s.push(b.into());
-                parse.next_byte();
+/// Parses `Infinity` or `NaN` after its first byte was consumed
+fn parse_non_finite<'a>(parse: &mut dyn Parse, first: u8, negative: bool) -> Result<'a> {
+    let x = match first {
+        b'I' => {
+            must_eat_ident(parse, b"nfinity")?;
+            f64::INFINITY
+        }
+        _ => {
+            must_eat_ident(parse, b"aN")?;
+            f64::NAN
+        }
+    };
+    Ok(Json::Number(Number::from(if negative { -x } else { x })))
+}
+
+fn parse_number(parse: &mut dyn Parse, byte: u8) -> Result<'_> {
+    let mut num = NumberScanner::new(byte, parse.options().allow_relaxed_numbers);
+    let step = loop {
+        let (n, step) = num.scan(parse.fill_buf()?);
+        parse.consume(n);
+        if let Some(step) = step {
+            break step;
+        }
+    };
+    if step == NumStep::Invalid {
+        let next = parse.peek_byte()?;
+        if let Some(b @ (b'I' | b'N')) = next {
+            if num.is_sign() && parse.options().allow_nan_and_infinity {
+                parse.consume_byte();
+                return parse_non_finite(parse, b, num.text() == "-");
             }
-            _ => break,
         }
+        if num.starts_hex()
+            && matches!(next, Some(b'x' | b'X'))
+            && parse.options().allow_hex_numbers
+        {
+            parse.consume_byte();
+            return parse_hex_number(parse, num.text() == "-0");
+        }
+        return Err(parse.invalid(SyntaxError::InvalidNumber));
     }
 
-    if let Ok(n) = s.parse::<u64>() {
-        return Ok(Json::Number(Number::from(n)));
-    }
+    num.finish(parse.options().number_mode, Json::Number, Json::RawNumber)
+        .map_err(|code| parse.error(code))
+}
 
-    if let Ok(n) = s.parse::<i64>() {
-        return Ok(Json::Number(Number::from(n)));
-    }
+/// Parses the digits of a hexadecimal integer, after its `0x` was consumed
+fn parse_hex_number(parse: &mut dyn Parse, negative: bool) -> Result<'_> {
+    let mut hex = HexScanner::new();
+    parse.scan_while(|b| b.is_ascii_hexdigit(), |run| hex.push(run))?;
+    if parse
+        .peek_byte()?
+        .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'.')
+    {
+        return Err(parse.invalid(SyntaxError::InvalidNumber));
+    }
+    hex.finish(
+        negative,
+        parse.options().number_mode,
+        Json::Number,
+        Json::RawNumber,
+    )
+    .map_err(|code| parse.error(code))
+}
 
-    let n = match s.parse::<f64>() {
-        Ok(n) => n,
-        Err(_) => return Err(SyntaxError::InvalidNumber.into()),
-    };
-    Ok(Json::Number(Number::from(n)))
+/// Converts the text of a valid number
+fn number_from_text(s: &str, is_integer: bool) -> std::result::Result<Number, SyntaxError> {
+    if is_integer {
+        if let Ok(n) = s.parse::<u64>() {
+            return Ok(Number::from(n));
+        }
+        if let Ok(n) = s.parse::<i64>() {
+            return Ok(Number::from(n));
+        }
+        if let Ok(n) = s.parse::<u128>() {
+            return Ok(Number::from(n));
+        }
+        if let Ok(n) = s.parse::<i128>() {
+            return Ok(Number::from(n));
+        }
+    }
+    match s.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(Number::from(n)),
+        _ => Err(SyntaxError::NumberOutOfRange),
+    }
 }
 
 /// Represents a JSON number (integer or float)
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Numbers compare by value, regardless of how they were written,
+/// so `Number::from(1)` is equal to `Number::from(1.0)`.
+#[derive(Debug, Clone, Copy)]
 pub struct Number {
     n: NumRepr,
 }
 
 // representation idea lifted from serde_json
-#[derive(Debug, Copy, Clone, PartialEq)]
+// the 128-bit variants only hold values outside of the 64-bit range
+#[derive(Debug, Copy, Clone)]
 enum NumRepr {
     PosInt(u64),
     NegInt(i64),
+    PosInt128(u128),
+    NegInt128(i128),
     Float(f64),
 }
 
+/// Any integer repr, widened for conversions and comparisons
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Int {
+    // declared first, so that every Neg is less than every Pos
+    Neg(i128),
+    Pos(u128),
+}
+
 // from serde_json
 macro_rules! impl_from_unsigned {
     ( $($ty:ty),* ) => {
@@ -238,6 +872,29 @@ macro_rules! impl_from_signed {
 impl_from_unsigned!(u8, u16, u32, u64, usize);
 impl_from_signed!(i8, i16, i32, i64, isize);
 
+impl From<u128> for Number {
+    fn from(u: u128) -> Self {
+        let n = match u64::try_from(u) {
+            Ok(u) => NumRepr::PosInt(u),
+            Err(_) => NumRepr::PosInt128(u),
+        };
+        Number { n }
+    }
+}
+
+impl From<i128> for Number {
+    fn from(i: i128) -> Self {
+        if i >= 0 {
+            return Number::from(i as u128);
+        }
+        let n = match i64::try_from(i) {
+            Ok(i) => NumRepr::NegInt(i),
+            Err(_) => NumRepr::NegInt128(i),
+        };
+        Number { n }
+    }
+}
+
 impl From<f64> for Number {
     fn from(float: f64) -> Self {
         Number {
@@ -254,10 +911,229 @@ impl From<f32> for Number {
     }
 }
 
+impl Number {
+    /// Returns true if the number was written without a fraction or exponent
+    pub fn is_integer(&self) -> bool {
+        !self.is_f64()
+    }
+
+    /// Returns true if the number is an integer that fits in a [`u64`]
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// Returns true if the number is an integer that fits in an [`i64`]
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Returns true if the number was written with a fraction or exponent
+    pub fn is_f64(&self) -> bool {
+        matches!(self.n, NumRepr::Float(_))
+    }
+
+    /// Returns the number if it is an integer that fits in a [`u64`]
+    pub fn as_u64(&self) -> Option<u64> {
+        u64::try_from(*self).ok()
+    }
+
+    /// Returns the number if it is an integer that fits in an [`i64`]
+    pub fn as_i64(&self) -> Option<i64> {
+        i64::try_from(*self).ok()
+    }
+
+    /// Returns the closest [`f64`] to this number. Large integers may lose precision.
+    pub fn as_f64(&self) -> f64 {
+        match self.n {
+            NumRepr::PosInt(u) => u as f64,
+            NumRepr::NegInt(i) => i as f64,
+            NumRepr::PosInt128(u) => u as f64,
+            NumRepr::NegInt128(i) => i as f64,
+            NumRepr::Float(f) => f,
+        }
+    }
+
+    fn int(&self) -> Option<Int> {
+        match self.n {
+            NumRepr::PosInt(u) => Some(Int::Pos(u.into())),
+            NumRepr::NegInt(i) => Some(Int::Neg(i.into())),
+            NumRepr::PosInt128(u) => Some(Int::Pos(u)),
+            NumRepr::NegInt128(i) => Some(Int::Neg(i)),
+            NumRepr::Float(_) => None,
+        }
+    }
+}
+
+impl From<Number> for f64 {
+    fn from(n: Number) -> Self {
+        n.as_f64()
+    }
+}
+
+/// Integers are converted if they fit in the target type.
+/// Floats are never converted, even if they have no fractional part.
+macro_rules! impl_try_from_number {
+    ( $($ty:ty),* ) => {
+        $(
+            impl TryFrom<Number> for $ty {
+                type Error = Error;
+
+                fn try_from(n: Number) -> std::result::Result<Self, Error> {
+                    let converted = match n.int() {
+                        Some(Int::Pos(u)) => <$ty>::try_from(u).ok(),
+                        Some(Int::Neg(i)) => <$ty>::try_from(i).ok(),
+                        None => None,
+                    };
+                    converted.ok_or_else(|| SyntaxError::NumberOutOfRange.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.n {
+            NumRepr::PosInt(u) => write!(f, "{}", u),
+            NumRepr::NegInt(i) => write!(f, "{}", i),
+            NumRepr::PosInt128(u) => write!(f, "{}", u),
+            NumRepr::NegInt128(i) => write!(f, "{}", i),
+            // unlike Display, Debug keeps the exponent and the `.0` of whole numbers
+            NumRepr::Float(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.int(), other.int()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            (Some(a), None) => cmp_int_float(a, other.as_f64()),
+            (None, Some(b)) => cmp_int_float(b, self.as_f64()).map(Ordering::reverse),
+            (None, None) => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+}
+
+/// Compares exactly, without rounding the integer to a float
+fn cmp_int_float(i: Int, f: f64) -> Option<Ordering> {
+    // both are powers of two, so they convert exactly
+    const POS_LIMIT: f64 = u128::MAX as f64;
+    const NEG_LIMIT: f64 = i128::MIN as f64;
+    if f.is_nan() {
+        return None;
+    }
+    let whole = f.trunc();
+    let ord = match i {
+        Int::Pos(_) if f < 0.0 => return Some(Ordering::Greater),
+        Int::Neg(_) if f >= 0.0 => return Some(Ordering::Less),
+        Int::Pos(_) if f >= POS_LIMIT => return Some(Ordering::Less),
+        Int::Neg(_) if f < NEG_LIMIT => return Some(Ordering::Greater),
+        Int::Pos(u) => u.cmp(&(whole as u128)),
+        Int::Neg(i) => i.cmp(&(whole as i128)),
+    };
+    match ord {
+        Ordering::Equal => 0.0.partial_cmp(&(f - whole)),
+        ord => Some(ord),
+    }
+}
+
+/// A JSON number kept as the exact text it was written as.
+/// Returned instead of [`Number`] when parsing with [`NumberMode::Raw`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawNumber(String);
+
+impl RawNumber {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Returns true if the number was written without a fraction or exponent
+    pub fn is_integer(&self) -> bool {
+        !self.0.bytes().any(|b| matches!(b, b'.' | b'e' | b'E'))
+    }
+
+    /// Converts to a [`Number`]. This loses precision for floats
+    /// and for integers that don't fit in 128 bits, the same as [`NumberMode::Number`].
+    ///
+    /// Fails with [`SyntaxError::NumberOutOfRange`] if the number is too large for an [`f64`].
+    pub fn to_number(&self) -> Result<'static, Number> {
+        Ok(number_from_text(&self.0, self.is_integer())?)
+    }
+}
+
+impl std::str::FromStr for RawNumber {
+    type Err = Error;
+
+    /// Checks that `s` is a single JSON number
+    fn from_str(s: &str) -> std::result::Result<Self, Error> {
+        let mut bytes = s.bytes();
+        let mut num = match bytes.next() {
+            Some(b @ (b'-' | b'0'..=b'9')) => NumberScanner::new(b, false),
+            _ => return Err(SyntaxError::InvalidNumber.into()),
+        };
+        if !bytes.all(|b| num.push(b) == NumStep::More) || !num.is_complete() {
+            return Err(SyntaxError::InvalidNumber.into());
+        }
+        Ok(RawNumber(s.to_owned()))
+    }
+}
+
+impl fmt::Display for RawNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for RawNumber {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Integers are converted if they fit in the target type, without going through a float.
+/// Fails with [`SyntaxError::NumberOutOfRange`] otherwise.
+macro_rules! impl_try_from_raw_number {
+    ( $($ty:ty),* ) => {
+        $(
+            impl TryFrom<&RawNumber> for $ty {
+                type Error = Error;
+
+                fn try_from(n: &RawNumber) -> std::result::Result<Self, Error> {
+                    <$ty>::try_from(n.to_number()?)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_raw_number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl TryFrom<&RawNumber> for f64 {
+    type Error = Error;
+
+    /// Fails if the number is too large for an [`f64`]. Precision may be lost otherwise.
+    fn try_from(n: &RawNumber) -> std::result::Result<Self, Error> {
+        n.to_number().map(f64::from)
+    }
+}
+
 pub struct ParseArray<'a> {
     parse: Option<&'a mut dyn Parse>,
     ended: bool, // required because of https://github.com/rust-lang/rust/issues/54663
-    needs_comma: bool,
+    commas: Commas,
 }
 
 use std::any::type_name;
@@ -307,7 +1183,7 @@ impl<'a> ParseArray<'a> {
         Self {
             parse: Some(parse),
             ended: false,
-            needs_comma: false,
+            commas: Commas::default(),
         }
     }
 
@@ -316,35 +1192,54 @@ impl<'a> ParseArray<'a> {
             return None;
         }
         let parse: &'b mut (dyn Parse + 'a) = *self.parse.as_mut().unwrap();
-        parse.do_skips();
+        if let Err(e) = parse.do_skips() {
+            return Some(Err(e));
+        }
         loop {
-            let b = parse.peek_byte()?;
+            let b = match parse.peek_byte() {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    parse.leave();
+                    self.ended = true;
+                    // the I/O error was already returned
+                    if parse.failed() {
+                        return None;
+                    }
+                    return Some(Err(parse.error(SyntaxError::EofWhileParsingList)));
+                }
+                Err(e) => return Some(Err(e)),
+            };
             match b {
                 b']' => {
-                    parse.next_byte();
+                    if let Err(code) = self.commas.close(parse.options()) {
+                        return Some(Err(parse.error(code)));
+                    }
+                    parse.consume_byte();
+                    parse.leave();
                     self.ended = true;
                     return None;
                 }
                 b',' => {
-                    parse.next_byte();
-                    if self.needs_comma {
-                        self.needs_comma = false;
-                        continue;
-                    } else {
-                        return Some(Err(SyntaxError::TrailingComma.into()));
+                    parse.consume_byte();
+                    if let Err(code) = self.commas.comma() {
+                        return Some(Err(parse.error(code)));
                     }
                 }
                 _ if b.is_ascii_whitespace() => {
-                    parse.next_byte();
+                    parse.consume_byte();
                     continue;
                 }
+                b'/' if parse.options().allow_comments => {
+                    if let Err(e) = parse.eat_whitespace() {
+                        self.commas.invalid();
+                        return Some(Err(e));
+                    }
+                }
                 _ => {
-                    if self.needs_comma {
-                        self.needs_comma = false;
-                        return Some(Err(SyntaxError::MissingComma.into()));
+                    if let Err(code) = self.commas.item(parse.options()) {
+                        return Some(Err(parse.error(code)));
                     }
-                    parse.next_byte();
-                    self.needs_comma = true;
+                    parse.consume_byte();
                     return Some(next_any_item(b, parse));
                 }
             }
@@ -360,14 +1255,55 @@ impl Drop for ParseArray<'_> {
     }
 }
 
-fn skip_array(parse: &mut dyn Parse) {
-    let mut arr = ParseArray::new(parse);
-    while arr.next().is_some() {}
+/// Tracks the commas between the items of an array or object
+#[derive(Debug, Default)]
+struct Commas {
+    // an item was read, so the next one must be preceded by a comma
+    needs_comma: bool,
+    // the last token was a comma that followed an item
+    after_comma: bool,
+}
+
+impl Commas {
+    /// Called after consuming a comma
+    fn comma(&mut self) -> std::result::Result<(), SyntaxError> {
+        if !self.needs_comma {
+            return Err(SyntaxError::TrailingComma);
+        }
+        self.needs_comma = false;
+        self.after_comma = true;
+        Ok(())
+    }
+
+    /// Called before an item. An error is only returned once, the item is read by the next call.
+    fn item(&mut self, options: &ParserOptions) -> std::result::Result<(), SyntaxError> {
+        self.after_comma = false;
+        if std::mem::replace(&mut self.needs_comma, true) && !options.allow_missing_commas {
+            self.needs_comma = false;
+            return Err(SyntaxError::MissingComma);
+        }
+        Ok(())
+    }
+
+    /// Called when a malformed token took the place of an item, so it isn't followed by a comma error
+    fn invalid(&mut self) {
+        self.needs_comma = true;
+        self.after_comma = false;
+    }
+
+    /// Called before the closing bracket. An error is only returned once.
+    fn close(&mut self, options: &ParserOptions) -> std::result::Result<(), SyntaxError> {
+        if std::mem::take(&mut self.after_comma) && !options.allow_trailing_commas {
+            return Err(SyntaxError::TrailingComma);
+        }
+        Ok(())
+    }
 }
 
 pub struct ParseObject<'a> {
     parse: Option<&'a mut dyn Parse>,
     ended: bool, // required because of https://github.com/rust-lang/rust/issues/54663
+    commas: Commas,
 }
 
 impl<'a> ParseObject<'a> {
@@ -375,239 +1311,799 @@ impl<'a> ParseObject<'a> {
         Self {
             parse: Some(parse),
             ended: false,
+            commas: Commas::default(),
+        }
+    }
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<'_, KeyVal<'_>>> {
+        if self.ended {
+            return None;
+        }
+        let parse: &mut dyn Parse = *self.parse.as_mut()?;
+        if let Err(e) = parse.do_skips() {
+            return Some(Err(e));
+        }
+        let delim = loop {
+            let b = match parse.peek_byte() {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    parse.leave();
+                    self.ended = true;
+                    // the I/O error was already returned
+                    if parse.failed() {
+                        return None;
+                    }
+                    return Some(Err(parse.error(SyntaxError::EofWhileParsingObject)));
+                }
+                Err(e) => return Some(Err(e)),
+            };
+            match b {
+                _ if b.is_ascii_whitespace() => {
+                    parse.consume_byte();
+                    continue;
+                }
+                b'/' if parse.options().allow_comments => {
+                    if let Err(e) = parse.eat_whitespace() {
+                        self.commas.invalid();
+                        return Some(Err(e));
+                    }
+                }
+                b',' => {
+                    parse.consume_byte();
+                    if let Err(code) = self.commas.comma() {
+                        return Some(Err(parse.error(code)));
+                    }
+                }
+                b'}' => {
+                    if let Err(code) = self.commas.close(parse.options()) {
+                        return Some(Err(parse.error(code)));
+                    }
+                    parse.consume_byte();
+                    parse.leave();
+                    self.ended = true;
+                    return None;
+                }
+                b'"' => break Some(DOUBLE_QUOTE),
+                b'\'' if parse.options().allow_single_quotes => break Some(SINGLE_QUOTE),
+                _ if parse.options().allow_bare_keys && is_ident_start(b) => {
+                    break Some(Delim::Bare)
+                }
+                _ => break None,
+            }
+        };
+        if let Err(code) = self.commas.item(parse.options()) {
+            return Some(Err(parse.error(code)));
+        }
+        let delim = match delim {
+            Some(delim) => delim,
+            None => {
+                return Some(Err(parse.invalid(SyntaxError::KeyMustBeAString)));
+            }
+        };
+        if delim != Delim::Bare {
+            parse.consume_byte();
+        }
+        Some(Ok(KeyVal::new(parse, delim)))
+    }
+}
+
+impl<'a> Drop for ParseObject<'a> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.parse.as_mut().unwrap().add_skip(Skip::Object);
+        }
+    }
+}
+
+/// Reads a key and/or value pair of an object.
+///
+/// They key and the value may be read independently, and either may be ignored.
+///
+/// For example, it's possible to only read the key, and ignore the value,
+/// which will be skipped efficiently.
+pub struct KeyVal<'a> {
+    // None here means the object is exhausted
+    parse: Option<&'a mut dyn Parse>,
+    key_consumed: bool,
+    key_delim: Delim,
+}
+
+impl<'a> KeyVal<'a> {
+    fn new(parse: &'a mut dyn Parse, key_delim: Delim) -> Self {
+        Self {
+            parse: Some(parse),
+            key_consumed: false,
+            key_delim,
+        }
+    }
+
+    /// Begins parsing the current object key.
+    /// Returns an error if called more than once.
+    pub fn key(&mut self) -> Result<'_, ParseString<'_>> {
+        if self.key_consumed {
+            return Err(Error::new(ErrorCode::KeyAlreadyRead, None));
+        }
+        self.key_consumed = true;
+        Ok(ParseString::new(
+            *self.parse.as_mut().unwrap(),
+            self.key_delim,
+        ))
+    }
+
+    /// Returns true if the key is equal to `s`, see [`ParseString::eq_str`].
+    /// Like [`key`](Self::key), this reads the key, and returns an error if it was already read.
+    pub fn eq_str(&mut self, s: &str) -> Result<'static, bool> {
+        self.key()?.eq_str(s)
+    }
+
+    /// Returns true if the key starts with `prefix`, see [`ParseString::starts_with`].
+    pub fn starts_with(&mut self, prefix: &str) -> Result<'static, bool> {
+        self.key()?.starts_with(prefix)
+    }
+
+    /// Returns the index of the first candidate that is equal to the key,
+    /// see [`ParseString::match_any`].
+    pub fn match_any(&mut self, candidates: &[&str]) -> Result<'static, Option<usize>> {
+        self.key()?.match_any(candidates)
+    }
+
+    /// Obtains a [`Json`] for this object value.
+    /// Skips and discards the key if it was not already retrieved.
+    pub fn value(mut self) -> Result<'a> {
+        let parse = self.parse.take().unwrap();
+        if !self.key_consumed {
+            parse.add_skip(Skip::String(self.key_delim));
+        }
+        read_value(parse)
+    }
+}
+
+impl<'a> Drop for KeyVal<'a> {
+    fn drop(&mut self) {
+        if let Some(parse) = self.parse.as_mut() {
+            if !self.key_consumed {
+                parse.add_skip(Skip::String(self.key_delim));
+            }
+            parse.add_skip(Skip::ObjectValue);
+        }
+    }
+}
+
+fn skip_obj_value(parse: &mut dyn Parse) -> Result<'static, ()> {
+    parse.eat_whitespace()?;
+    match parse.peek_byte()? {
+        Some(b':') => parse.consume_byte(),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedColon)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingObject)),
+    }
+    parse.eat_whitespace()?;
+
+    let skipper = match parse.peek_byte()? {
+        Some(b'"') => Skipper::string(DOUBLE_QUOTE, parse.options()),
+        Some(b'\'') if parse.options().allow_single_quotes => {
+            Skipper::string(SINGLE_QUOTE, parse.options())
+        }
+        Some(open @ (b'[' | b'{')) => Skipper::nested(open, parse.options()),
+        Some(b) if is_scalar_byte(b) => return parse.scan_while(is_scalar_byte, |_| ()),
+        // left for the object to handle
+        Some(b',' | b'}') => return Err(parse.error(SyntaxError::ExpectedSomeValue)),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedSomeValue)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingValue)),
+    };
+    parse.consume_byte();
+    parse.skip(skipper)
+}
+
+/// Resynchronizes after malformed input, by consuming bytes until the next `,`, `]` or `}`
+/// that is not nested deeper than the current level. The delimiter itself is left for the array or object.
+///
+/// Outside of any array or object, values are only separated by whitespace, so that also ends the skip.
+fn skip_entry(parse: &mut dyn Parse) -> Result<'static, ()> {
+    let top_level = *parse.depth() == 0;
+    parse.skip(Skipper::entry(top_level, parse.options()))
+}
+
+fn read_value(parse: &mut dyn Parse) -> Result<'_> {
+    // the key is skipped here if it wasn't read to the end
+    parse.do_skips()?;
+
+    parse.eat_whitespace()?;
+    match parse.peek_byte()? {
+        Some(b':') => parse.consume_byte(),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedColon)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingObject)),
+    }
+    parse.eat_whitespace()?;
+
+    match parse.peek_byte()? {
+        // left for the object to handle
+        Some(b',' | b'}') => Err(parse.error(SyntaxError::ExpectedSomeValue)),
+        Some(b) => {
+            parse.consume_byte();
+            next_any_item(b, parse)
+        }
+        None => Err(parse.error(SyntaxError::EofWhileParsingValue)),
+    }
+}
+
+/// Reads a string. Reading can be done as a whole string,
+/// or char-by-char if the string is expected to be very large.
+pub struct ParseString<'a> {
+    parse: Option<&'a mut dyn Parse>,
+    delim: Delim,
+}
+
+impl<'a> ParseString<'a> {
+    fn new(parse: &'a mut dyn Parse, delim: Delim) -> Self {
+        Self {
+            parse: Some(parse),
+            delim,
+        }
+    }
+
+    /// Parses the entire JSON string into a new [`String`]
+    pub fn read_owned(self) -> Result<'static, String> {
+        let mut buf = String::new();
+        self.read_into(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Parses the entire string into the supplied [`String`].
+    /// This is useful to avoid allocating a new String,
+    /// or for preallocating a buffer when string length can be guessed.
+    ///
+    /// If an error is returned, `buf` is left as it was,
+    /// and the rest of the string is skipped.
+    pub fn read_into(mut self, buf: &mut String) -> Result<'_, ()> {
+        let parse = self.parse.take().unwrap();
+        let start = buf.len();
+        loop {
+            let part = read_str_part(parse, self.delim, &mut |s| {
+                buf.push_str(s);
+                s.len()
+            });
+            match part {
+                Ok(StrPart::Raw) => {}
+                Ok(StrPart::Char(c)) => buf.push(c),
+                Ok(StrPart::End) => return Ok(()),
+                Err(e) => {
+                    buf.truncate(start);
+                    parse.skip_string_after(self.delim, &e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Parses the entire string, borrowing it from the parser's buffer when possible.
+    ///
+    /// This avoids a copy when the whole string is already buffered and has no escapes,
+    /// which is always the case for a parser created with [`Parser::from_slice`] on a string without escapes.
+    /// Otherwise, the string is decoded into a new [`String`].
+    ///
+    /// A borrowed string stays in the parser's buffer until the following call to `fn next`,
+    /// so [`Parser::position`] only moves past it then.
+    pub fn read_cow(mut self) -> Result<'a, Cow<'a, str>> {
+        let parse = self.parse.take().unwrap();
+        let controls = parse.options().allow_control_characters;
+        let buf = parse.fill_buf()?;
+        let (end, closing_len) = match self.delim {
+            Delim::Quote(q) => match buf.iter().position(|&b| ends_raw_part(b, q, controls)) {
+                Some(i) if buf[i] == q => (i, 1),
+                _ => {
+                    return ParseString::new(parse, self.delim)
+                        .read_owned()
+                        .map(Cow::Owned)
+                }
+            },
+            Delim::Bare => match buf.iter().position(|&b| !is_ident_byte(b)) {
+                Some(i) => (i, 0),
+                None => {
+                    return ParseString::new(parse, self.delim)
+                        .read_owned()
+                        .map(Cow::Owned)
+                }
+            },
+        };
+
+        let pos = parse.position();
+        parse.add_skip(Skip::Bytes(end + closing_len));
+        match std::str::from_utf8(&parse.fill_buf()?[..end]) {
+            Ok(s) => Ok(Cow::Borrowed(s)),
+            Err(_) => Err(Error::new(
+                ErrorCode::Syntax(SyntaxError::InvalidUnicodeCodePoint),
+                Some(pos),
+            )),
         }
     }
-    pub fn next(&mut self) -> Option<Result<KeyVal>> {
-        if self.ended {
-            return None;
-        }
-        let parse: &mut dyn Parse = *self.parse.as_mut()?;
-        parse.do_skips();
+
+    /// Parses this JSON string one [`char`] at a time,
+    /// instead of the entire string.
+    pub fn read_chars(mut self) -> ParseChars<'a> {
+        ParseChars::new(self.parse.take().unwrap(), self.delim)
+    }
+
+    /// Parses this JSON string as an [`io::Read`] of its decoded UTF-8 bytes,
+    /// so that a huge string can be copied elsewhere without holding all of it in memory.
+    pub fn read_bytes(mut self) -> ParseBytes<'a> {
+        ParseBytes::new(self.parse.take().unwrap(), self.delim)
+    }
+
+    /// Returns true if this string is equal to `s`.
+    ///
+    /// Like [`match_any`](Self::match_any), this compares the decoded string without allocating.
+    pub fn eq_str(self, s: &str) -> Result<'static, bool> {
+        self.find(&[s], false).map(|i| i.is_some())
+    }
+
+    /// Returns true if this string starts with `prefix`.
+    /// Only reads the string up to the end of `prefix`, and the rest of it is skipped.
+    pub fn starts_with(self, prefix: &str) -> Result<'static, bool> {
+        self.find(&[prefix], true).map(|i| i.is_some())
+    }
+
+    /// Returns the index of the first candidate that is equal to this string, if any.
+    ///
+    /// The string is decoded and compared as it is read, without building a [`String`].
+    /// As soon as no candidate can match anymore, the rest of the string is skipped.
+    pub fn match_any(self, candidates: &[&str]) -> Result<'static, Option<usize>> {
+        self.find(candidates, false)
+    }
+
+    /// Compares the string to `candidates` one byte at a time, stopping early once the result is known.
+    /// With `prefix`, a candidate matches once it was read completely.
+    fn find(mut self, candidates: &[&str], prefix: bool) -> Result<'static, Option<usize>> {
+        let parse = self.parse.take().unwrap();
+        let mut m = Candidates::new(candidates);
         loop {
-            let b = parse.peek_byte()?;
-            match b {
-                _ if b.is_ascii_whitespace() || b == b',' => {
-                    parse.next_byte();
-                    continue;
+            let mut stopped = false;
+            let mut push = |b| {
+                if (prefix && m.complete()) || !m.push(b) {
+                    stopped = true;
                 }
-                b'}' => {
-                    parse.next_byte();
-                    self.ended = true;
-                    return None;
+                !stopped
+            };
+            let part = read_str_part(parse, self.delim, &mut |s| {
+                s.bytes().position(|b| !push(b)).unwrap_or(s.len())
+            });
+            match part {
+                Ok(StrPart::Raw) => {}
+                Ok(StrPart::Char(c)) => {
+                    for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                        if !push(b) {
+                            break;
+                        }
+                    }
                 }
-                b'"' => {
-                    parse.next_byte();
-                    break;
+                Ok(StrPart::End) if prefix => return Ok(m.complete().then_some(0)),
+                Ok(StrPart::End) => return Ok(m.end()),
+                Err(e) => {
+                    parse.skip_string_after(self.delim, &e);
+                    return Err(e);
                 }
-                _ => panic!("unhandled char '{}' in object", char::from(b)),
+            }
+            // either the prefix was read, or nothing matches anymore
+            if stopped {
+                parse.add_skip(Skip::String(self.delim));
+                return Ok(m.complete().then_some(0));
             }
         }
-        Some(Ok(KeyVal::new(parse)))
+    }
+
+    /// Parses this JSON string as base64, returning an [`io::Read`] of the decoded bytes.
+    ///
+    /// Padding is optional, but must be correct if present. Whitespace is not allowed.
+    pub fn read_base64(mut self, alphabet: Alphabet) -> ParseBase64<'a> {
+        ParseBase64::new(self.parse.take().unwrap(), self.delim, alphabet)
     }
 }
 
-impl<'a> Drop for ParseObject<'a> {
+impl Drop for ParseString<'_> {
     fn drop(&mut self) {
-        if !self.ended {
-            self.parse.as_mut().unwrap().add_skip(Skip::Object);
+        if let Some(p) = self.parse.as_mut() {
+            p.add_skip(Skip::String(self.delim));
         }
     }
 }
 
-fn skip_obj(parse: &mut dyn Parse) {
-    let mut obj = ParseObject::new(parse);
-    while obj.next().is_some() {}
-}
-
-/// Reads a key and/or value pair of an object.
-///
-/// They key and the value may be read independently, and either may be ignored.
+/// Tracks which candidates of `ParseString::match_any` still match the bytes read so far.
 ///
-/// For example, it's possible to only read the key, and ignore the value,
-/// which will be skipped efficiently.
-pub struct KeyVal<'a> {
-    // None here means the object is exhausted
-    parse: Option<&'a mut dyn Parse>,
-    key_consumed: bool,
+/// Those bytes are not stored: they are always the first `len` bytes of the candidate at `index`,
+/// which is the first one that still matches.
+struct Candidates<'c> {
+    candidates: &'c [&'c str],
+    index: usize,
+    len: usize,
 }
 
-impl<'a> KeyVal<'a> {
-    fn new(parse: &'a mut dyn Parse) -> Self {
+impl<'c> Candidates<'c> {
+    fn new(candidates: &'c [&'c str]) -> Self {
         Self {
-            parse: Some(parse),
-            key_consumed: false,
+            candidates,
+            index: 0,
+            len: 0,
         }
     }
 
-    /// Begins parsing the current object key.
-    /// Panics if called more than once.
-    pub fn key(&mut self) -> ParseString {
-        assert!(!self.key_consumed);
-        self.key_consumed = true;
-        ParseString::new(*self.parse.as_mut().unwrap())
+    /// Returns false if no candidate continues with `b`
+    fn push(&mut self, b: u8) -> bool {
+        let Some(current) = self.candidates.get(self.index) else {
+            return false;
+        };
+        let read = &current.as_bytes()[..self.len];
+        let next = self.candidates[self.index..].iter().position(|c| {
+            let c = c.as_bytes();
+            c.get(self.len) == Some(&b) && c.starts_with(read)
+        });
+        match next {
+            Some(i) => {
+                self.index += i;
+                self.len += 1;
+                true
+            }
+            None => {
+                self.index = self.candidates.len();
+                false
+            }
+        }
     }
 
-    /// Obtains a [`Json`] for this object value.
-    /// Skips and discards the key if it was not already retrieved.
-    pub fn value(mut self) -> Result<'a> {
-        let parse = self.parse.take().unwrap();
-        read_value(parse, self.key_consumed)
+    /// Returns true if the current candidate was read completely
+    fn complete(&self) -> bool {
+        self.candidates
+            .get(self.index)
+            .is_some_and(|c| c.len() == self.len)
     }
-}
 
-impl<'a> Drop for KeyVal<'a> {
-    fn drop(&mut self) {
-        if let Some(parse) = self.parse.as_mut() {
-            parse.add_skip(Skip::ObjectValue {
-                key_consumed: self.key_consumed,
-            });
-        }
+    /// Returns the index of the first candidate that is equal to the bytes read
+    fn end(&self) -> Option<usize> {
+        let current = self.candidates.get(self.index)?;
+        let read = &current.as_bytes()[..self.len];
+        let i = self.candidates[self.index..]
+            .iter()
+            .position(|c| c.as_bytes() == read)?;
+        Some(self.index + i)
     }
 }
 
-fn skip_obj_value(parse: &mut dyn Parse, key_consumed: bool) {
-    let v = loop {
-        if let Ok(v) = read_value(parse, key_consumed) {
-            break v;
-        }
+/// The next decoded part of a string, as returned by `read_str_part`
+enum StrPart {
+    /// Unescaped text, which was passed to the caller's closure
+    Raw,
+    /// An escaped char, or one that was split across buffer refills
+    Char(char),
+    /// The closing quote was consumed
+    End,
+}
+
+/// Decodes the next part of a string. Unescaped text is validated and passed to `raw`,
+/// which returns how many of its bytes it used. Only those bytes are consumed.
+fn read_str_part(
+    parse: &mut dyn Parse,
+    delim: Delim,
+    raw: &mut dyn FnMut(&str) -> usize,
+) -> Result<'static, StrPart> {
+    let quote = match delim {
+        Delim::Quote(q) => q,
+        Delim::Bare => return read_bare_part(parse, raw),
     };
-    match v {
-        Json::String(mut p) => {
-            p.skip();
-        }
-        Json::Array(mut p) => while p.next().is_some() {},
-        Json::Object(mut p) => while p.next().is_some() {},
-        _ => {}
+    let controls = parse.options().allow_control_characters;
+    let buf = parse.fill_buf()?;
+    let end = buf
+        .iter()
+        .position(|&b| ends_raw_part(b, quote, controls))
+        .unwrap_or(buf.len());
+    let text = match std::str::from_utf8(&buf[..end]) {
+        Ok(s) => s,
+        Err(e) => std::str::from_utf8(&buf[..e.valid_up_to()]).unwrap(),
+    };
+    if text.is_empty() {
+        // a quote, escape or control char, or a char that is invalid or continues past the end of the buffer
+        return read_char(parse, delim).map(|c| c.map_or(StrPart::End, StrPart::Char));
     }
+    let used = raw(text);
+    parse.consume(used);
+    Ok(StrPart::Raw)
 }
 
-fn read_value(parse: &mut dyn Parse, key_consumed: bool) -> Result {
-    if !key_consumed {
-        skip_string(parse);
-    }
+/// Returns true if `b` can't be passed on as part of the unescaped text of a string
+fn ends_raw_part(b: u8, quote: u8, allow_control_characters: bool) -> bool {
+    b == quote || b == b'\\' || (b < 0x20 && !allow_control_characters)
+}
 
-    parse.eat_whitespace();
-    assert_eq!(parse.next_byte(), Some(b':'));
-    parse.eat_whitespace();
+/// Same as `read_str_part`, for an unquoted key
+fn read_bare_part(
+    parse: &mut dyn Parse,
+    raw: &mut dyn FnMut(&str) -> usize,
+) -> Result<'static, StrPart> {
+    let buf = parse.fill_buf()?;
+    let n = buf.iter().take_while(|&&b| is_ident_byte(b)).count();
+    if n == 0 {
+        return Ok(StrPart::End);
+    }
+    // identifiers are ascii
+    let used = raw(std::str::from_utf8(&buf[..n]).unwrap());
+    parse.consume(used);
+    Ok(StrPart::Raw)
+}
 
-    let b = match parse.next_byte() {
-        Some(b) => b,
-        _ => return Err(SyntaxError::EofWhileParsingValue.into()),
-    };
-    next_any_item(b, parse)
+/// Decodes the next char of a string, or returns None after consuming the closing quote
+fn read_char(parse: &mut dyn Parse, delim: Delim) -> Result<'static, Option<char>> {
+    let mut decoder = CharDecoder::new(delim, parse.options());
+    loop {
+        let (n, decoded) = decoder.scan(parse.fill_buf()?);
+        parse.consume(n);
+        match decoded {
+            Some(Decoded::Char(c)) => return Ok(Some(c)),
+            Some(Decoded::End) => return Ok(None),
+            Some(Decoded::Invalid(code)) => return Err(parse.error(code)),
+            None => {}
+        }
+    }
 }
 
-/// Reads a string. Reading can be done as a whole string,
-/// or char-by-char if the string is expected to be very large.
-pub struct ParseString<'a> {
-    parse: Option<&'a mut dyn Parse>,
+/// Yields the decoded chars of a string.
+///
+/// After an error, the rest of the string is skipped and the iterator ends.
+pub struct ParseChars<'a> {
+    parse: &'a mut dyn Parse,
+    delim: Delim,
+    done: bool,
 }
 
-impl<'a> ParseString<'a> {
-    fn new(parse: &'a mut dyn Parse) -> Self {
-        Self { parse: Some(parse) }
+impl<'a> ParseChars<'a> {
+    fn new(parse: &'a mut dyn Parse, delim: Delim) -> Self {
+        Self {
+            parse,
+            delim,
+            done: false,
+        }
     }
+}
 
-    /// Parses the entire JSON string into a new [`String`]
-    pub fn read_owned(self) -> Result<'static, String> {
-        let mut buf = String::new();
-        self.read_into(&mut buf)?;
-        Ok(buf)
-    }
+impl<'a> Iterator for ParseChars<'a> {
+    type Item = Result<'static, char>;
 
-    /// Parses the entire string into the supplied [`String`].
-    /// This is useful to avoid allocating a new String,
-    /// or for preallocating a buffer when string length can be guessed.
-    pub fn read_into(mut self, buf: &mut String) -> Result<()> {
-        let parse = self.parse.take().unwrap();
-        let mut escape = false;
-        while let Some(b) = parse.next_byte() {
-            match b {
-                b'\\' if !escape => escape = true,
-                b'"' if !escape => return Ok(()),
-                c => {
-                    escape = false;
-                    buf.push(c.into());
-                }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match read_char(self.parse, self.delim) {
+            Ok(c) => {
+                self.done = c.is_none();
+                c.map(Ok)
+            }
+            Err(e) => {
+                self.done = true;
+                self.parse.skip_string_after(self.delim, &e);
+                Some(Err(e))
             }
         }
-        Err(SyntaxError::EofWhileParsingString.into())
     }
+}
 
-    /// Parses this JSON string one [`char`] at a time,
-    /// instead of the entire string.
-    pub fn read_chars(mut self) -> ParseChars<'a> {
-        ParseChars::new(self.parse.take().unwrap())
+impl Drop for ParseChars<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.parse.add_skip(Skip::String(self.delim));
+        }
     }
+}
+
+/// Reads the decoded UTF-8 bytes of a string, see [`ParseString::read_bytes`].
+///
+/// Invalid input is returned as an [`io::Error`] wrapping the parser's [`Error`], after which
+/// the rest of the string is skipped. If this is dropped early, the rest of the string is skipped too.
+pub struct ParseBytes<'a> {
+    parse: &'a mut dyn Parse,
+    delim: Delim,
+    done: bool,
+    // a decoded char that did not fit into the buffer of the last read
+    char_buf: [u8; 4],
+    char_start: usize,
+    char_end: usize,
+}
 
-    fn skip(&mut self) {
-        skip_string(*self.parse.as_mut().unwrap());
+impl<'a> ParseBytes<'a> {
+    fn new(parse: &'a mut dyn Parse, delim: Delim) -> Self {
+        Self {
+            parse,
+            delim,
+            done: false,
+            char_buf: [0; 4],
+            char_start: 0,
+            char_end: 0,
+        }
     }
 }
 
-impl Drop for ParseString<'_> {
-    fn drop(&mut self) {
-        if let Some(p) = self.parse.as_mut() {
-            p.add_skip(Skip::String);
+impl Read for ParseBytes<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.char_start < self.char_end {
+            let n = (self.char_end - self.char_start).min(out.len());
+            out[..n].copy_from_slice(&self.char_buf[self.char_start..self.char_start + n]);
+            self.char_start += n;
+            return Ok(n);
+        }
+        if self.done || out.is_empty() {
+            return Ok(0);
+        }
+
+        let mut copied = 0;
+        let part = read_str_part(self.parse, self.delim, &mut |s| {
+            // only whole chars are consumed, so the parser never stops inside one
+            let mut n = s.len().min(out.len());
+            while !s.is_char_boundary(n) {
+                n -= 1;
+            }
+            out[..n].copy_from_slice(&s.as_bytes()[..n]);
+            copied = n;
+            n
+        });
+        let c = match part {
+            Ok(StrPart::Raw) if copied > 0 => return Ok(copied),
+            // the next char is longer than `out`, so it is decoded on its own
+            Ok(StrPart::Raw) => read_char(self.parse, self.delim),
+            Ok(StrPart::Char(c)) => Ok(Some(c)),
+            Ok(StrPart::End) => Ok(None),
+            Err(e) => Err(e),
+        };
+        match c {
+            Ok(Some(c)) => {
+                self.char_start = 0;
+                self.char_end = c.encode_utf8(&mut self.char_buf).len();
+                self.read(out)
+            }
+            Ok(None) => {
+                self.done = true;
+                Ok(0)
+            }
+            Err(e) => {
+                self.done = true;
+                self.parse.skip_string_after(self.delim, &e);
+                Err(e.into())
+            }
         }
     }
 }
 
-fn skip_string(parse: &mut dyn Parse) {
-    let mut escape = false;
-    while let Some(b) = parse.next_byte() {
-        match b {
-            b'\\' if !escape => escape = true,
-            b'"' if !escape => return,
-            _ => escape = false,
+impl Drop for ParseBytes<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.parse.add_skip(Skip::String(self.delim));
         }
     }
 }
 
-pub struct ParseChars<'a> {
+/// Reads the base64-decoded bytes of a string, see [`ParseString::read_base64`].
+///
+/// Invalid input is returned as an [`io::Error`] wrapping the parser's [`Error`], such as
+/// [`SyntaxError::InvalidBase64`], after which the rest of the string is skipped.
+/// If this is dropped early, the rest of the string is skipped too.
+pub struct ParseBase64<'a> {
     parse: &'a mut dyn Parse,
+    delim: Delim,
+    done: bool,
+    decoder: Decoder,
+    // decoded bytes that did not fit into the buffer of the last read
+    group: [u8; 3],
+    group_start: usize,
+    group_end: usize,
+    // an error found after some bytes were already decoded by the last read
+    error: Option<Error>,
 }
 
-impl<'a> ParseChars<'a> {
-    fn new(parse: &'a mut dyn Parse) -> Self {
-        Self { parse }
+impl<'a> ParseBase64<'a> {
+    fn new(parse: &'a mut dyn Parse, delim: Delim, alphabet: Alphabet) -> Self {
+        Self {
+            parse,
+            delim,
+            done: false,
+            decoder: Decoder::new(alphabet),
+            group: [0; 3],
+            group_start: 0,
+            group_end: 0,
+            error: None,
+        }
     }
 
-    fn unicode_escape(&mut self) -> Option<char> {
-        let mut val = 0u32;
-        loop {
-            match self.parse.next_byte()? {
-                c @ b'0'..=b'9' => val = val * 16 + u32::from(c - b'0'),
-                c @ b'a'..=b'f' => val = val * 16 + u32::from(c - b'a') + 10,
-                b'}' => return char::try_from(val).ok(),
-                _ => return None,
-            }
+    /// Copies as much of `group` as fits into `out[*copied..]`, and keeps the rest for the next read
+    fn output(&mut self, (group, len): Group, out: &mut [u8], copied: &mut usize) {
+        let n = len.min(out.len() - *copied);
+        out[*copied..*copied + n].copy_from_slice(&group[..n]);
+        *copied += n;
+        self.group = group;
+        self.group_start = n;
+        self.group_end = len;
+    }
+
+    /// Skips the rest of the string, unless its end was already reached
+    fn fail(&mut self, e: Error) -> io::Error {
+        if !std::mem::replace(&mut self.done, true) {
+            self.parse.skip_string_after(self.delim, &e);
         }
+        e.into()
     }
 }
 
-impl<'a> Iterator for ParseChars<'a> {
-    type Item = char;
+impl Read for ParseBase64<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.group_start < self.group_end {
+            let n = (self.group_end - self.group_start).min(out.len());
+            out[..n].copy_from_slice(&self.group[self.group_start..self.group_start + n]);
+            self.group_start += n;
+            return Ok(n);
+        }
+        if let Some(e) = self.error.take() {
+            return Err(self.fail(e));
+        }
+        if self.done || out.is_empty() {
+            return Ok(0);
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut escape = false;
+        let mut copied = 0;
         loop {
-            match self.parse.next_byte()? {
-                b'\\' if !escape => {
-                    escape = true;
-                    continue;
+            let mut invalid = None;
+            let decoder = &mut self.decoder;
+            let (group, group_start, group_end) =
+                (&mut self.group, &mut self.group_start, &mut self.group_end);
+            let part = read_str_part(self.parse, self.delim, &mut |s| {
+                for (i, b) in s.bytes().enumerate() {
+                    match decoder.push(b) {
+                        Ok(None) => {}
+                        Ok(Some((g, len))) => {
+                            let n = len.min(out.len() - copied);
+                            out[copied..copied + n].copy_from_slice(&g[..n]);
+                            copied += n;
+                            if n < len || copied == out.len() {
+                                (*group, *group_start, *group_end) = (g, n, len);
+                                return i + 1;
+                            }
+                        }
+                        Err(e) => {
+                            // the error points at the invalid symbol
+                            invalid = Some(e);
+                            return i;
+                        }
+                    }
+                }
+                s.len()
+            });
+            let pushed = match (invalid, part) {
+                (Some(e), _) => Err(self.parse.error(e)),
+                (None, Ok(StrPart::Raw)) => Ok(None),
+                // an escaped symbol, such as `\/`
+                (None, Ok(StrPart::Char(c))) if c.is_ascii() => {
+                    self.decoder.push(c as u8).map_err(|e| self.parse.error(e))
+                }
+                (None, Ok(StrPart::Char(_))) => Err(self.parse.error(SyntaxError::InvalidBase64)),
+                (None, Ok(StrPart::End)) => {
+                    self.done = true;
+                    self.decoder.finish().map_err(|e| self.parse.error(e))
+                }
+                (None, Err(e)) => Err(e),
+            };
+            match pushed {
+                Ok(Some(group)) => self.output(group, out, &mut copied),
+                Ok(None) => {}
+                // the bytes before the error are returned first
+                Err(e) if copied > 0 => {
+                    self.error = Some(e);
+                    return Ok(copied);
                 }
-                b'"' if !escape => return None,
-                b'r' if escape => return Some('\r'),
-                b'u' if escape => match self.parse.next_byte()? {
-                    b'{' => match self.unicode_escape() {
-                        Some(c) => return Some(c),
-                        _ => continue,
-                    },
-                    _ => continue,
-                },
-                c => return Some(c.into()),
+                Err(e) => return Err(self.fail(e)),
             }
+            if copied == out.len() || self.group_start < self.group_end || self.done {
+                return Ok(copied);
+            }
+        }
+    }
+}
+
+impl Drop for ParseBase64<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.parse.add_skip(Skip::String(self.delim));
         }
     }
 }
@@ -619,6 +2115,8 @@ pub enum Json<'a> {
     Null,
     Bool(bool),
     Number(Number),
+    /// Only returned with [`NumberMode::Raw`]
+    RawNumber(RawNumber),
     String(ParseString<'a>),
     Array(ParseArray<'a>),
     Object(ParseObject<'a>),
@@ -628,6 +2126,7 @@ mod private {
     pub trait Sealed {}
 }
 
+#[allow(clippy::wrong_self_convention)]
 pub trait JsonAccess<'a>: private::Sealed {
     #[inline]
     fn is_null(&self) -> bool {
@@ -642,6 +2141,7 @@ pub trait JsonAccess<'a>: private::Sealed {
         self.as_number().is_some()
     }
 
+    fn is_raw_number(&self) -> bool;
     fn is_string(&self) -> bool;
     fn is_array(&self) -> bool;
     fn is_object(&self) -> bool;
@@ -650,6 +2150,7 @@ pub trait JsonAccess<'a>: private::Sealed {
     fn as_bool(&self) -> Option<bool>;
     fn as_number(&self) -> Option<Number>;
 
+    fn as_raw_number(self) -> Option<RawNumber>;
     fn as_string(self) -> Option<ParseString<'a>>;
     fn as_array(self) -> Option<ParseArray<'a>>;
     fn as_object(self) -> Option<ParseObject<'a>>;
@@ -678,6 +2179,18 @@ impl<'a> JsonAccess<'a> for Json<'a> {
         }
     }
 
+    #[inline]
+    fn is_raw_number(&self) -> bool {
+        matches!(self, Self::RawNumber(_))
+    }
+
+    fn as_raw_number(self) -> Option<RawNumber> {
+        match self {
+            Self::RawNumber(n) => Some(n),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn is_string(&self) -> bool {
         matches!(self, Self::String(_))
@@ -729,6 +2242,18 @@ impl<'a> JsonAccess<'a> for Result<'a> {
         self.as_ref().ok()?.as_number()
     }
 
+    #[inline]
+    fn is_raw_number(&self) -> bool {
+        match self {
+            Ok(j) => j.is_raw_number(),
+            _ => false,
+        }
+    }
+
+    fn as_raw_number(self) -> Option<RawNumber> {
+        self.ok().and_then(Json::as_raw_number)
+    }
+
     #[inline]
     fn is_string(&self) -> bool {
         match self {
@@ -768,6 +2293,14 @@ impl<'a> JsonAccess<'a> for Result<'a> {
 
 impl<'a> private::Sealed for Option<Result<'a>> {}
 impl<'a> JsonAccess<'a> for Option<Result<'a>> {
+    #[inline(always)]
+    fn is_raw_number(&self) -> bool {
+        match self {
+            Some(r) => r.is_raw_number(),
+            _ => false,
+        }
+    }
+
     #[inline(always)]
     fn is_string(&self) -> bool {
         match self {
@@ -813,6 +2346,13 @@ impl<'a> JsonAccess<'a> for Option<Result<'a>> {
         }
     }
 
+    fn as_raw_number(self) -> Option<RawNumber> {
+        match self {
+            Some(Ok(Json::RawNumber(n))) => Some(n),
+            _ => None,
+        }
+    }
+
     fn as_string(self) -> Option<ParseString<'a>> {
         match self {
             Some(Ok(Json::String(s))) => Some(s),
@@ -835,36 +2375,223 @@ impl<'a> JsonAccess<'a> for Option<Result<'a>> {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// A location in the parsed input.
+///
+/// Lines and columns start at 1, and columns count chars, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    offset: u64,
+    line: u64,
+    column: u64,
+}
+
+impl Position {
+    /// The number of bytes read before this position.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    #[inline]
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    #[inline]
+    pub fn column(&self) -> u64 {
+        self.column
+    }
+
+    fn advance_by(&mut self, bytes: &[u8]) {
+        // continuation bytes of multi-byte chars don't start a new column
+        let is_char_start = |b: &&u8| **b & 0xc0 != 0x80;
+        self.offset += bytes.len() as u64;
+        match bytes.iter().rposition(|b| *b == b'\n') {
+            Some(last) => {
+                self.line += bytes[..=last].iter().filter(|b| **b == b'\n').count() as u64;
+                self.column = 1 + bytes[last + 1..].iter().filter(is_char_start).count() as u64;
+            }
+            None => self.column += bytes.iter().filter(is_char_start).count() as u64,
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+/// An error returned while parsing.
+///
+/// Most errors are recoverable: the invalid input is skipped, and the next call to `next`
+/// continues with the following value. See [`Error::is_fatal`] for the ones that are not.
+#[derive(Debug)]
 pub struct Error {
-    err: Box<ErrorCode>,
+    err: Box<ErrorImpl>,
+}
+
+#[derive(Debug)]
+struct ErrorImpl {
+    code: ErrorCode,
+    position: Option<Position>,
 }
 
 impl Error {
+    fn new(code: ErrorCode, position: Option<Position>) -> Self {
+        Self {
+            err: Box::new(ErrorImpl { code, position }),
+        }
+    }
+
     pub fn syntax(&self) -> Option<SyntaxError> {
-        match *self.err {
+        match self.err.code {
             ErrorCode::Syntax(s) => Some(s),
-            // _ => None,
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying I/O error, if reading from the source failed.
+    pub fn io(&self) -> Option<&io::Error> {
+        match &self.err.code {
+            ErrorCode::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of the parser when this error was detected.
+    ///
+    /// This is `None` for errors that did not come from parsing input.
+    #[inline]
+    pub fn position(&self) -> Option<Position> {
+        self.err.position
+    }
+
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        self.io().is_some()
+    }
+
+    /// Returns true if no more values can be parsed after this error,
+    /// because reading failed, the input ended in the middle of a value,
+    /// or a [single document](Parser::single) was followed by more input.
+    ///
+    /// After any other error, the parser resynchronizes and parsing may continue:
+    /// - a string with an invalid escape or invalid UTF-8 is skipped up to its closing quote
+    /// - a missing or extra comma is reported on its own, and the item after it is still returned
+    /// - a value nested deeper than [`ParserOptions::max_depth`] is skipped
+    /// - otherwise, the rest of the invalid array item or object entry is skipped up to
+    ///   the next `,`, `]` or `}` at the same depth. Outside of any array or object,
+    ///   it is skipped up to the next whitespace.
+    pub fn is_fatal(&self) -> bool {
+        match self.err.code {
+            ErrorCode::Io(_) => true,
+            ErrorCode::Syntax(s) => matches!(
+                s,
+                SyntaxError::EofWhileParsingList
+                    | SyntaxError::EofWhileParsingObject
+                    | SyntaxError::EofWhileParsingString
+                    | SyntaxError::EofWhileParsingValue
+                    | SyntaxError::TrailingCharacters
+            ),
+            ErrorCode::KeyAlreadyRead => false,
+        }
+    }
+}
+
+/// I/O errors compare equal if they are of the same [`ErrorKind`].
+/// Positions are not compared.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.err.code, &other.err.code) {
+            (ErrorCode::Syntax(a), ErrorCode::Syntax(b)) => a == b,
+            (ErrorCode::Io(a), ErrorCode::Io(b)) => a.kind() == b.kind(),
+            (ErrorCode::KeyAlreadyRead, ErrorCode::KeyAlreadyRead) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.err.code {
+            ErrorCode::Syntax(s) => write!(f, "syntax error: {:?}", s)?,
+            ErrorCode::Io(e) => write!(f, "io error: {}", e)?,
+            ErrorCode::KeyAlreadyRead => write!(f, "object key was already read")?,
+        }
+        match self.err.position {
+            Some(pos) => write!(f, " at {}", pos),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.err.code {
+            ErrorCode::Io(e) => Some(e),
+            _ => None,
         }
     }
 }
 
 impl From<SyntaxError> for Error {
     fn from(e: SyntaxError) -> Self {
-        Self {
-            err: Box::new(ErrorCode::Syntax(e)),
-        }
+        Self::new(ErrorCode::Syntax(e), None)
+    }
+}
+
+impl From<Error> for io::Error {
+    /// I/O errors are unwrapped. Other errors are wrapped with [`ErrorKind::UnexpectedEof`]
+    /// if the input ended too early, or [`ErrorKind::InvalidData`] otherwise.
+    fn from(e: Error) -> Self {
+        let kind = match &e.err.code {
+            ErrorCode::Io(_) => {
+                let ErrorCode::Io(io) = e.err.code else {
+                    unreachable!()
+                };
+                return io;
+            }
+            ErrorCode::Syntax(
+                SyntaxError::EofWhileParsingList
+                | SyntaxError::EofWhileParsingObject
+                | SyntaxError::EofWhileParsingString
+                | SyntaxError::EofWhileParsingValue,
+            ) => ErrorKind::UnexpectedEof,
+            ErrorCode::Syntax(_) => ErrorKind::InvalidData,
+            ErrorCode::KeyAlreadyRead => ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::new(ErrorCode::Io(e), None)
     }
 }
 
 // Modeled after serde_json
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub(crate) enum ErrorCode {
     /// Catchall for syntax error messages
     // Message(Box<str>),
 
-    // Io(io::Error),
+    /// Reading from the underlying source failed
+    Io(io::Error),
     Syntax(SyntaxError),
+    /// [`KeyVal::key`] was called more than once
+    KeyAlreadyRead,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -892,17 +2619,11 @@ pub enum SyntaxError {
     /// Expected this character to be a `':'`.
     ExpectedColon,
 
-    /// Expected this character to be either a `','` or a `']'`.
-    // ExpectedListCommaOrEnd,
-
-    /// Expected this character to be either a `','` or a `'}'`.
-    // ExpectedObjectCommaOrEnd,
-
-    /// Expected to parse either a `true`, `false`, or a `null`.
-    // ExpectedSomeIdent,
-
     /// Expected this character to start a JSON value.
-    // ExpectedSomeValue,
+    ExpectedSomeValue,
+
+    /// A `/` that does not start a `//` or `/*` comment.
+    InvalidComment,
 
     /// Invalid hex escape code.
     InvalidEscape,
@@ -931,10 +2652,19 @@ pub enum SyntaxError {
     /// JSON has non-whitespace trailing characters after the value.
     TrailingCharacters,
 
+    /// Top-level values were not separated as the parser's [`StreamMode`] requires.
+    MissingSeparator,
+
     /// Unexpected end of hex excape.
     UnexpectedEndOfHexEscape,
 
-    /// Encountered nesting of JSON maps and arrays more than 128 layers deep.
+    /// A character outside of the base64 alphabet in a string read with [`ParseString::read_base64`].
+    InvalidBase64,
+
+    /// Misplaced or missing `=` padding in a string read with [`ParseString::read_base64`].
+    InvalidBase64Padding,
+
+    /// Encountered nesting of JSON maps and arrays deeper than [`ParserOptions::max_depth`].
     RecursionLimitExceeded,
 }
 
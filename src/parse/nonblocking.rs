@@ -0,0 +1,883 @@
+//! Async counterparts of [`Parser`](super::Parser) and its subparsers.
+//!
+//! An [`AsyncParser`] reads from anything implementing [`AsyncRead`],
+//! and yields [`AsyncJson`] values. Subparsers behave the same as their sync versions,
+//! including the lazy skipping of dropped items on the following call to `fn next`.
+//!
+//! Since a [`Drop`] impl cannot await, skipping is done without recursion,
+//! by scanning bytes and counting nesting levels.
+//! Numbers, strings and skipped input are read by the same state machines as the sync parser.
+//!
+//! The futures returned here are [`Send`] whenever the reader is.
+
+use super::scan::{CharDecoder, Decoded, HexScanner, NumStep, NumberScanner, Skipper};
+use super::{
+    is_ident_start, is_scalar_byte, Commas, Delim, Error, ErrorCode, Number, ParserOptions,
+    Position, RawNumber, Roots, Skip, StreamMode, SyntaxError, DOUBLE_QUOTE, SINGLE_QUOTE,
+};
+use std::any::type_name;
+use std::fmt::{self, Debug, Formatter};
+use std::future::poll_fn;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::ptr;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncBufRead, AsyncRead, BufReader};
+
+/// Reads bytes from an [`AsyncRead`], parses them as [`AsyncJson`], and returns a stream of values or sub-parsers via `async fn next()`
+pub struct AsyncParser<R> {
+    src: BufReader<R>,
+    skips: Vec<Skip>,
+    // set after an I/O error, from then on the input is treated as ended
+    failed: bool,
+    pos: Position,
+    options: ParserOptions,
+    // arrays and objects that were opened and not yet closed or skipped
+    depth: usize,
+    roots: Roots,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+impl<R: AsyncRead + Unpin> AsyncParser<R> {
+    /// Constructs a new AsyncParser that will read from the provided object.
+    pub fn new(r: R) -> Self {
+        Self {
+            src: BufReader::new(r),
+            skips: vec![],
+            failed: false,
+            pos: Position::default(),
+            options: ParserOptions::default(),
+            depth: 0,
+            roots: Roots::new(StreamMode::default()),
+        }
+    }
+
+    /// Replaces the default options
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Only accepts a single JSON document, same as [`Parser::single`](super::Parser::single).
+    pub fn single(self) -> Self {
+        self.stream_mode(StreamMode::Single)
+    }
+
+    /// Sets how top-level values are expected to be separated, same as [`Parser::stream_mode`](super::Parser::stream_mode).
+    pub fn stream_mode(mut self, mode: StreamMode) -> Self {
+        self.roots = Roots::new(mode);
+        self
+    }
+
+    /// Returns the position of the next byte that will be read.
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    /// Returns the next JSON item.
+    /// An AsyncParser will read any number of JSON items and return them in order,
+    /// separated as its [`StreamMode`] requires, or only one with `single`.
+    /// Returns None when the input is exhausted.
+    ///
+    /// If reading fails, the I/O error is returned, and the parser
+    /// (including any live subparsers) behaves as if the input ended there.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<AsyncJson<'_, R>>> {
+        if let Err(e) = self.do_skips().await {
+            return Some(Err(e));
+        }
+        let end_of_last = self.pos;
+        if let Err(e) = self.eat_whitespace().await {
+            return Some(Err(e));
+        }
+        let b = match self.peek_byte().await {
+            Ok(b) => b,
+            Err(e) => return Some(Err(e)),
+        };
+        match self.roots.next(b, end_of_last, self.pos)? {
+            Ok(()) => {
+                self.consume_byte();
+                Some(next_any_item(b?, self).await)
+            }
+            Err(code) => Some(Err(Error::new(ErrorCode::Syntax(code), Some(self.pos)))),
+        }
+    }
+
+    /// Returns the buffered input, reading more if the buffer is empty.
+    /// An empty slice means the input has ended.
+    fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        if self.failed {
+            return Poll::Ready(Ok(&[]));
+        }
+        match Pin::new(&mut self.src).poll_fill_buf(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Ok(_)) => {}
+            Poll::Ready(Err(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Poll::Ready(Ok(&[]))
+            }
+            Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(Err(e)) => {
+                self.failed = true;
+                self.skips.clear();
+                return Poll::Ready(Err(Error::new(ErrorCode::Io(e), Some(self.pos))));
+            }
+        }
+        // returning the buffer from inside the match doesn't pass the borrow checker,
+        // but it stays filled until it is consumed
+        Poll::Ready(Ok(self.src.buffer()))
+    }
+
+    /// Advances past `n` bytes of the slice returned by `poll_fill_buf`
+    fn consume(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.pos.advance_by(&self.src.buffer()[..n]);
+        Pin::new(&mut self.src).consume(n);
+    }
+
+    /// Passes the buffered input to `f`, which returns how many of its bytes it used, and a result.
+    /// Only those bytes are consumed. An empty slice means the input has ended.
+    async fn scan<T>(&mut self, mut f: impl FnMut(&[u8]) -> (usize, T)) -> Result<T> {
+        poll_fn(|cx| {
+            let (n, t) = f(ready!(self.poll_fill_buf(cx))?);
+            self.consume(n);
+            Poll::Ready(Ok(t))
+        })
+        .await
+    }
+
+    async fn peek_byte(&mut self) -> Result<Option<u8>> {
+        self.scan(|buf| (0, buf.first().copied())).await
+    }
+
+    /// Advances past a byte that was returned by `peek_byte`
+    fn consume_byte(&mut self) {
+        self.consume(1);
+    }
+
+    /// Consumes bytes while `pred` holds, passing them to `f` one buffered run at a time
+    async fn scan_while(
+        &mut self,
+        pred: impl Fn(u8) -> bool,
+        mut f: impl FnMut(&[u8]),
+    ) -> Result<()> {
+        loop {
+            let more = self
+                .scan(|buf| {
+                    let n = buf.iter().take_while(|b| pred(**b)).count();
+                    f(&buf[..n]);
+                    (n, n > 0 && n == buf.len())
+                })
+                .await?;
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consumes input until `skipper` is done
+    async fn skip(&mut self, mut skipper: Skipper) -> Result<()> {
+        loop {
+            if let Some(done) = self.scan(|buf| skipper.scan(buf)).await? {
+                return done.map_err(|e| self.error(e));
+            }
+        }
+    }
+
+    /// Consumes whitespace, and comments if they are allowed
+    async fn eat_whitespace(&mut self) -> Result<()> {
+        loop {
+            self.scan_while(|b| b.is_ascii_whitespace(), |_| ()).await?;
+            if !(self.options.allow_comments && self.peek_byte().await? == Some(b'/')) {
+                return Ok(());
+            }
+            self.consume_byte();
+            match self.peek_byte().await? {
+                Some(kind @ (b'/' | b'*')) => {
+                    self.consume_byte();
+                    self.skip(Skipper::comment(kind, &self.options)).await?;
+                }
+                _ => return Err(self.invalid(SyntaxError::InvalidComment)),
+            }
+        }
+    }
+
+    async fn do_skips(&mut self) -> Result<()> {
+        for skip in std::mem::take(&mut self.skips) {
+            match skip {
+                Skip::String(delim) => self.skip(Skipper::string(delim, &self.options)).await?,
+                Skip::Array => {
+                    let skipped = self.skip(Skipper::nested(b'[', &self.options)).await;
+                    self.leave();
+                    skipped?;
+                }
+                Skip::Object => {
+                    let skipped = self.skip(Skipper::nested(b'{', &self.options)).await;
+                    self.leave();
+                    skipped?;
+                }
+                Skip::ObjectValue => skip_obj_value(self).await?,
+                Skip::Bytes(n) => self.consume(n),
+                Skip::Entry => skip_entry(self).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> AsyncParser<R> {
+    /// Builds an error located at the current position
+    fn error(&self, code: SyntaxError) -> Error {
+        Error::new(ErrorCode::Syntax(code), Some(self.pos))
+    }
+
+    /// Builds an error for malformed input located at the current position.
+    /// The rest of the invalid item is skipped by the next call to `next`, see `skip_entry`.
+    fn invalid(&mut self, code: SyntaxError) -> Error {
+        let e = self.error(code);
+        self.add_skip(Skip::Entry);
+        e
+    }
+
+    /// Skips the rest of a string after `e` was returned while reading it.
+    /// If the input ended inside the string, that was already reported by `e`.
+    fn skip_string_after(&mut self, delim: Delim, e: &Error) {
+        if e.syntax() != Some(SyntaxError::EofWhileParsingString) {
+            self.add_skip(Skip::String(delim));
+        }
+    }
+
+    /// Called after the opening bracket of an array or object was consumed.
+    /// If that is too deep, the value is skipped instead, using `skip`.
+    fn enter(&mut self, skip: Skip) -> Result<()> {
+        self.depth += 1;
+        if self.depth > self.options.max_depth {
+            // the skip closes this level again
+            let e = self.error(SyntaxError::RecursionLimitExceeded);
+            self.add_skip(skip);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Called after the closing bracket of an array or object was consumed
+    fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn add_skip(&mut self, s: Skip) {
+        if !self.failed {
+            self.skips.push(s);
+        }
+    }
+}
+
+async fn next_any_item<R: AsyncRead + Unpin>(
+    b: u8,
+    parse: &mut AsyncParser<R>,
+) -> Result<AsyncJson<'_, R>> {
+    match b {
+        b'0'..=b'9' | b'-' => parse_number(parse, b).await,
+        b'.' | b'+' if parse.options.allow_relaxed_numbers => parse_number(parse, b).await,
+        b'n' => {
+            must_eat_ident(parse, b"ull").await?;
+            Ok(AsyncJson::Null)
+        }
+        b't' => {
+            must_eat_ident(parse, b"rue").await?;
+            Ok(AsyncJson::Bool(true))
+        }
+        b'f' => {
+            must_eat_ident(parse, b"alse").await?;
+            Ok(AsyncJson::Bool(false))
+        }
+        b'[' => {
+            parse.enter(Skip::Array)?;
+            Ok(AsyncJson::Array(AsyncParseArray::new(parse)))
+        }
+        b'{' => {
+            parse.enter(Skip::Object)?;
+            Ok(AsyncJson::Object(AsyncParseObject::new(parse)))
+        }
+        b'"' => Ok(AsyncJson::String(AsyncParseString::new(
+            parse,
+            DOUBLE_QUOTE,
+        ))),
+        b'\'' if parse.options.allow_single_quotes => Ok(AsyncJson::String(AsyncParseString::new(
+            parse,
+            SINGLE_QUOTE,
+        ))),
+        b'I' | b'N' if parse.options.allow_nan_and_infinity => {
+            parse_non_finite(parse, b, false).await
+        }
+        b if b.is_ascii_alphabetic() => Err(parse.invalid(SyntaxError::InvalidIdentifier)),
+        b'.' | b'+' => Err(parse.invalid(SyntaxError::InvalidNumber)),
+        _ => Err(parse.invalid(SyntaxError::ExpectedSomeValue)),
+    }
+}
+
+async fn must_eat_ident<R: AsyncRead + Unpin>(
+    parse: &mut AsyncParser<R>,
+    ident: &[u8],
+) -> Result<()> {
+    for b in ident {
+        let read = match parse.peek_byte().await? {
+            Some(b) => b,
+            _ => return Err(parse.error(SyntaxError::EofWhileParsingValue)),
+        };
+        if *b != read {
+            // a delimiter right after the identifier is left in place
+            if read.is_ascii_alphanumeric() {
+                parse.consume_byte();
+            }
+            return Err(parse.invalid(SyntaxError::InvalidIdentifier));
+        }
+        parse.consume_byte();
+    }
+    Ok(())
+}
+
+/// Parses `Infinity` or `NaN` after its first byte was consumed
+async fn parse_non_finite<R: AsyncRead + Unpin>(
+    parse: &mut AsyncParser<R>,
+    first: u8,
+    negative: bool,
+) -> Result<AsyncJson<'_, R>> {
+    let x = match first {
+        b'I' => {
+            must_eat_ident(parse, b"nfinity").await?;
+            f64::INFINITY
+        }
+        _ => {
+            must_eat_ident(parse, b"aN").await?;
+            f64::NAN
+        }
+    };
+    Ok(AsyncJson::Number(Number::from(if negative {
+        -x
+    } else {
+        x
+    })))
+}
+
+async fn parse_number<R: AsyncRead + Unpin>(
+    parse: &mut AsyncParser<R>,
+    byte: u8,
+) -> Result<AsyncJson<'_, R>> {
+    let mut num = NumberScanner::new(byte, parse.options.allow_relaxed_numbers);
+    let step = loop {
+        if let Some(step) = parse.scan(|buf| num.scan(buf)).await? {
+            break step;
+        }
+    };
+    if step == NumStep::Invalid {
+        let next = parse.peek_byte().await?;
+        if let Some(b @ (b'I' | b'N')) = next {
+            if num.is_sign() && parse.options.allow_nan_and_infinity {
+                parse.consume_byte();
+                return parse_non_finite(parse, b, num.text() == "-").await;
+            }
+        }
+        if num.starts_hex() && matches!(next, Some(b'x' | b'X')) && parse.options.allow_hex_numbers
+        {
+            parse.consume_byte();
+            return parse_hex_number(parse, num.text() == "-0").await;
+        }
+        return Err(parse.invalid(SyntaxError::InvalidNumber));
+    }
+
+    num.finish(
+        parse.options.number_mode,
+        AsyncJson::Number,
+        AsyncJson::RawNumber,
+    )
+    .map_err(|code| parse.error(code))
+}
+
+/// Parses the digits of a hexadecimal integer, after its `0x` was consumed
+async fn parse_hex_number<R: AsyncRead + Unpin>(
+    parse: &mut AsyncParser<R>,
+    negative: bool,
+) -> Result<AsyncJson<'_, R>> {
+    let mut hex = HexScanner::new();
+    parse
+        .scan_while(|b| b.is_ascii_hexdigit(), |run| hex.push(run))
+        .await?;
+    if parse
+        .peek_byte()
+        .await?
+        .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'.')
+    {
+        return Err(parse.invalid(SyntaxError::InvalidNumber));
+    }
+    hex.finish(
+        negative,
+        parse.options.number_mode,
+        AsyncJson::Number,
+        AsyncJson::RawNumber,
+    )
+    .map_err(|code| parse.error(code))
+}
+
+/// The async version of [`skip_obj_value`](super::skip_obj_value)
+async fn skip_obj_value<R: AsyncRead + Unpin>(parse: &mut AsyncParser<R>) -> Result<()> {
+    parse.eat_whitespace().await?;
+    match parse.peek_byte().await? {
+        Some(b':') => parse.consume_byte(),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedColon)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingObject)),
+    }
+    parse.eat_whitespace().await?;
+
+    let skipper = match parse.peek_byte().await? {
+        Some(b'"') => Skipper::string(DOUBLE_QUOTE, &parse.options),
+        Some(b'\'') if parse.options.allow_single_quotes => {
+            Skipper::string(SINGLE_QUOTE, &parse.options)
+        }
+        Some(open @ (b'[' | b'{')) => Skipper::nested(open, &parse.options),
+        Some(b) if is_scalar_byte(b) => return parse.scan_while(is_scalar_byte, |_| ()).await,
+        // left for the object to handle
+        Some(b',' | b'}') => return Err(parse.error(SyntaxError::ExpectedSomeValue)),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedSomeValue)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingValue)),
+    };
+    parse.consume_byte();
+    parse.skip(skipper).await
+}
+
+/// The async version of [`skip_entry`](super::skip_entry)
+async fn skip_entry<R: AsyncRead + Unpin>(parse: &mut AsyncParser<R>) -> Result<()> {
+    let top_level = parse.depth == 0;
+    parse.skip(Skipper::entry(top_level, &parse.options)).await
+}
+
+/// Represents a json value (null, bool, numbers),
+/// or holds an async parser that yields a larger value (string, array, object)
+pub enum AsyncJson<'a, R> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    /// Only returned with [`NumberMode::Raw`](super::NumberMode::Raw)
+    RawNumber(RawNumber),
+    String(AsyncParseString<'a, R>),
+    Array(AsyncParseArray<'a, R>),
+    Object(AsyncParseObject<'a, R>),
+}
+
+// not derived, which would require the reader to be Debug
+impl<R> Debug for AsyncJson<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AsyncJson::Null => f.write_str("Null"),
+            AsyncJson::Bool(b) => f.debug_tuple("Bool").field(b).finish(),
+            AsyncJson::Number(n) => f.debug_tuple("Number").field(n).finish(),
+            AsyncJson::RawNumber(n) => f.debug_tuple("RawNumber").field(n).finish(),
+            AsyncJson::String(s) => f.debug_tuple("String").field(s).finish(),
+            AsyncJson::Array(a) => f.debug_tuple("Array").field(a).finish(),
+            AsyncJson::Object(o) => f.debug_tuple("Object").field(o).finish(),
+        }
+    }
+}
+
+macro_rules! impl_debug_for_subparser {
+    ( $($ty:ident),* ) => {
+        $(
+            impl<R> Debug for $ty<'_, R> {
+                fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    write!(
+                        f,
+                        "<{} for AsyncParser@{:p}>",
+                        type_name::<Self>(),
+                        self.parse
+                            .as_ref()
+                            .map(|p| *p as *const AsyncParser<R> as *const ())
+                            .unwrap_or(ptr::null())
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_debug_for_subparser!(AsyncParseString, AsyncParseArray, AsyncParseObject);
+
+pub struct AsyncParseArray<'a, R> {
+    parse: Option<&'a mut AsyncParser<R>>,
+    ended: bool,
+    commas: Commas,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncParseArray<'a, R> {
+    fn new(parse: &'a mut AsyncParser<R>) -> Self {
+        Self {
+            parse: Some(parse),
+            ended: false,
+            commas: Commas::default(),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<AsyncJson<'_, R>>> {
+        if self.ended {
+            return None;
+        }
+        let parse: &mut AsyncParser<R> = self.parse.as_mut().unwrap();
+        if let Err(e) = parse.do_skips().await {
+            return Some(Err(e));
+        }
+        loop {
+            let b = match parse.peek_byte().await {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    parse.leave();
+                    self.ended = true;
+                    // the I/O error was already returned
+                    if parse.failed {
+                        return None;
+                    }
+                    return Some(Err(parse.error(SyntaxError::EofWhileParsingList)));
+                }
+                Err(e) => return Some(Err(e)),
+            };
+            match b {
+                b']' => {
+                    if let Err(code) = self.commas.close(&parse.options) {
+                        return Some(Err(parse.error(code)));
+                    }
+                    parse.consume_byte();
+                    parse.leave();
+                    self.ended = true;
+                    return None;
+                }
+                b',' => {
+                    parse.consume_byte();
+                    if let Err(code) = self.commas.comma() {
+                        return Some(Err(parse.error(code)));
+                    }
+                }
+                _ if b.is_ascii_whitespace() => {
+                    parse.consume_byte();
+                    continue;
+                }
+                b'/' if parse.options.allow_comments => {
+                    if let Err(e) = parse.eat_whitespace().await {
+                        self.commas.invalid();
+                        return Some(Err(e));
+                    }
+                }
+                _ => {
+                    if let Err(code) = self.commas.item(&parse.options) {
+                        return Some(Err(parse.error(code)));
+                    }
+                    parse.consume_byte();
+                    return Some(next_any_item(b, parse).await);
+                }
+            }
+        }
+    }
+}
+
+impl<R> Drop for AsyncParseArray<'_, R> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.parse.as_mut().unwrap().add_skip(Skip::Array);
+        }
+    }
+}
+
+pub struct AsyncParseObject<'a, R> {
+    parse: Option<&'a mut AsyncParser<R>>,
+    ended: bool,
+    commas: Commas,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncParseObject<'a, R> {
+    fn new(parse: &'a mut AsyncParser<R>) -> Self {
+        Self {
+            parse: Some(parse),
+            ended: false,
+            commas: Commas::default(),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<AsyncKeyVal<'_, R>>> {
+        if self.ended {
+            return None;
+        }
+        let parse: &mut AsyncParser<R> = self.parse.as_mut()?;
+        if let Err(e) = parse.do_skips().await {
+            return Some(Err(e));
+        }
+        let delim = loop {
+            let b = match parse.peek_byte().await {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    parse.leave();
+                    self.ended = true;
+                    // the I/O error was already returned
+                    if parse.failed {
+                        return None;
+                    }
+                    return Some(Err(parse.error(SyntaxError::EofWhileParsingObject)));
+                }
+                Err(e) => return Some(Err(e)),
+            };
+            match b {
+                _ if b.is_ascii_whitespace() => {
+                    parse.consume_byte();
+                    continue;
+                }
+                b'/' if parse.options.allow_comments => {
+                    if let Err(e) = parse.eat_whitespace().await {
+                        self.commas.invalid();
+                        return Some(Err(e));
+                    }
+                }
+                b',' => {
+                    parse.consume_byte();
+                    if let Err(code) = self.commas.comma() {
+                        return Some(Err(parse.error(code)));
+                    }
+                }
+                b'}' => {
+                    if let Err(code) = self.commas.close(&parse.options) {
+                        return Some(Err(parse.error(code)));
+                    }
+                    parse.consume_byte();
+                    parse.leave();
+                    self.ended = true;
+                    return None;
+                }
+                b'"' => break Some(DOUBLE_QUOTE),
+                b'\'' if parse.options.allow_single_quotes => break Some(SINGLE_QUOTE),
+                _ if parse.options.allow_bare_keys && is_ident_start(b) => break Some(Delim::Bare),
+                _ => break None,
+            }
+        };
+        if let Err(code) = self.commas.item(&parse.options) {
+            return Some(Err(parse.error(code)));
+        }
+        let delim = match delim {
+            Some(delim) => delim,
+            None => {
+                return Some(Err(parse.invalid(SyntaxError::KeyMustBeAString)));
+            }
+        };
+        if delim != Delim::Bare {
+            parse.consume_byte();
+        }
+        Some(Ok(AsyncKeyVal::new(parse, delim)))
+    }
+}
+
+impl<R> Drop for AsyncParseObject<'_, R> {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.parse.as_mut().unwrap().add_skip(Skip::Object);
+        }
+    }
+}
+
+/// Reads a key and/or value pair of an object.
+///
+/// The async counterpart of [`KeyVal`](super::KeyVal).
+pub struct AsyncKeyVal<'a, R> {
+    // None here means the value was taken
+    parse: Option<&'a mut AsyncParser<R>>,
+    key_consumed: bool,
+    key_delim: Delim,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncKeyVal<'a, R> {
+    fn new(parse: &'a mut AsyncParser<R>, key_delim: Delim) -> Self {
+        Self {
+            parse: Some(parse),
+            key_consumed: false,
+            key_delim,
+        }
+    }
+
+    /// Begins parsing the current object key.
+    /// Returns an error if called more than once.
+    pub fn key(&mut self) -> Result<AsyncParseString<'_, R>> {
+        if self.key_consumed {
+            return Err(Error::new(ErrorCode::KeyAlreadyRead, None));
+        }
+        self.key_consumed = true;
+        Ok(AsyncParseString::new(
+            self.parse.as_mut().unwrap(),
+            self.key_delim,
+        ))
+    }
+
+    /// Obtains an [`AsyncJson`] for this object value.
+    /// Skips and discards the key if it was not already retrieved.
+    pub async fn value(mut self) -> Result<AsyncJson<'a, R>> {
+        let parse = self.parse.take().unwrap();
+        if !self.key_consumed {
+            parse.add_skip(Skip::String(self.key_delim));
+        }
+        read_value(parse).await
+    }
+}
+
+impl<R> Drop for AsyncKeyVal<'_, R> {
+    fn drop(&mut self) {
+        if let Some(parse) = self.parse.as_mut() {
+            if !self.key_consumed {
+                parse.add_skip(Skip::String(self.key_delim));
+            }
+            parse.add_skip(Skip::ObjectValue);
+        }
+    }
+}
+
+async fn read_value<R: AsyncRead + Unpin>(parse: &mut AsyncParser<R>) -> Result<AsyncJson<'_, R>> {
+    // the key is skipped here if it wasn't read to the end
+    parse.do_skips().await?;
+
+    parse.eat_whitespace().await?;
+    match parse.peek_byte().await? {
+        Some(b':') => parse.consume_byte(),
+        Some(_) => return Err(parse.invalid(SyntaxError::ExpectedColon)),
+        None => return Err(parse.error(SyntaxError::EofWhileParsingObject)),
+    }
+    parse.eat_whitespace().await?;
+
+    match parse.peek_byte().await? {
+        // left for the object to handle
+        Some(b',' | b'}') => Err(parse.error(SyntaxError::ExpectedSomeValue)),
+        Some(b) => {
+            parse.consume_byte();
+            next_any_item(b, parse).await
+        }
+        None => Err(parse.error(SyntaxError::EofWhileParsingValue)),
+    }
+}
+
+/// Reads a string. Reading can be done as a whole string,
+/// or char-by-char if the string is expected to be very large.
+pub struct AsyncParseString<'a, R> {
+    parse: Option<&'a mut AsyncParser<R>>,
+    delim: Delim,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncParseString<'a, R> {
+    fn new(parse: &'a mut AsyncParser<R>, delim: Delim) -> Self {
+        Self {
+            parse: Some(parse),
+            delim,
+        }
+    }
+
+    /// Parses the entire JSON string into a new [`String`]
+    pub async fn read_owned(self) -> Result<String> {
+        let mut buf = String::new();
+        self.read_into(&mut buf).await?;
+        Ok(buf)
+    }
+
+    /// Parses the entire string into the supplied [`String`].
+    /// This is useful to avoid allocating a new String,
+    /// or for preallocating a buffer when string length can be guessed.
+    ///
+    /// If an error is returned, `buf` is left as it was,
+    /// and the rest of the string is skipped.
+    pub async fn read_into(mut self, buf: &mut String) -> Result<()> {
+        let parse = self.parse.take().unwrap();
+        let start = buf.len();
+        loop {
+            match read_char(parse, self.delim).await {
+                Ok(Some(c)) => buf.push(c),
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    buf.truncate(start);
+                    parse.skip_string_after(self.delim, &e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Parses this JSON string one [`char`] at a time,
+    /// instead of the entire string.
+    pub fn read_chars(mut self) -> AsyncParseChars<'a, R> {
+        AsyncParseChars::new(self.parse.take().unwrap(), self.delim)
+    }
+}
+
+impl<R> Drop for AsyncParseString<'_, R> {
+    fn drop(&mut self) {
+        if let Some(p) = self.parse.as_mut() {
+            p.add_skip(Skip::String(self.delim));
+        }
+    }
+}
+
+/// Decodes the next char of a string, or returns None after consuming the closing quote
+async fn read_char<R: AsyncRead + Unpin>(
+    parse: &mut AsyncParser<R>,
+    delim: Delim,
+) -> Result<Option<char>> {
+    let mut decoder = CharDecoder::new(delim, &parse.options);
+    loop {
+        match parse.scan(|buf| decoder.scan(buf)).await? {
+            Some(Decoded::Char(c)) => return Ok(Some(c)),
+            Some(Decoded::End) => return Ok(None),
+            Some(Decoded::Invalid(code)) => return Err(parse.error(code)),
+            None => {}
+        }
+    }
+}
+
+/// Yields the decoded chars of a string via `async fn next()`.
+///
+/// After an error, the rest of the string is skipped and no more chars are returned.
+pub struct AsyncParseChars<'a, R> {
+    parse: &'a mut AsyncParser<R>,
+    delim: Delim,
+    done: bool,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncParseChars<'a, R> {
+    fn new(parse: &'a mut AsyncParser<R>, delim: Delim) -> Self {
+        Self {
+            parse,
+            delim,
+            done: false,
+        }
+    }
+
+    /// Returns the next char of the string, or None once the closing quote was read.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<char>> {
+        if self.done {
+            return None;
+        }
+        match read_char(self.parse, self.delim).await {
+            Ok(c) => {
+                self.done = c.is_none();
+                c.map(Ok)
+            }
+            Err(e) => {
+                self.done = true;
+                self.parse.skip_string_after(self.delim, &e);
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<R> Drop for AsyncParseChars<'_, R> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.parse.add_skip(Skip::String(self.delim));
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Readers for streams of JSON values that are framed by a delimiter byte:
+//! [JSON Lines](https://jsonlines.org), also known as NDJSON, and
+//! [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464) JSON text sequences.
+//!
+//! The underlying [`Parser`] only sees one record at a time,
+//! so a malformed or truncated record cannot affect the records after it.
+
+use super::{
+    starts_scalar, Error, ErrorCode, Json, Parse, Parser, ParserOptions, Position, Result, Roots,
+    StreamMode, SyntaxError,
+};
+use std::io::{BufRead, BufReader, Read};
+
+/// The ASCII record separator that starts each JSON text of a sequence
+const RS: u8 = 0x1e;
+
+/// Reads newline-delimited JSON, and returns the value of each line via `fn next()`.
+///
+/// Every error belongs to the line it was found on. Errors that end a [`Parser`], like
+/// [`SyntaxError::EofWhileParsingList`] or [`SyntaxError::EofWhileParsingString`] for a truncated line,
+/// only end that line, and reading resumes at the next one.
+/// Only I/O errors end the whole input.
+pub struct JsonLines<R: BufRead> {
+    records: Records<R>,
+    // the line of the last value returned by `next`
+    line: u64,
+}
+
+impl<R: Read> JsonLines<BufReader<R>> {
+    /// Constructs a new JsonLines that will read from the provided object.
+    /// Reads are buffered internally, so `r` does not need to be buffered.
+    pub fn new(r: R) -> Self {
+        Self::from_buf_read(BufReader::new(r))
+    }
+}
+
+impl<R: BufRead> JsonLines<R> {
+    /// Constructs a new JsonLines that will read from the provided object,
+    /// using its buffer directly.
+    pub fn from_buf_read(r: R) -> Self {
+        Self {
+            records: Records::new(r, b'\n'),
+            line: 0,
+        }
+    }
+
+    /// Replaces the default options
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.records.parser.options = options;
+        self
+    }
+
+    /// Returns the position of the next byte that will be read.
+    pub fn position(&self) -> Position {
+        self.records.parser.position()
+    }
+
+    /// Returns the next line's value, along with its line number, starting at 1.
+    /// Blank lines are ignored, and None is returned when the input is exhausted.
+    ///
+    /// Anything left of the previous line is skipped first. If that line held more than
+    /// a single value, [`SyntaxError::TrailingCharacters`] is returned for it before moving on.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(u64, Result<'_>)> {
+        if let Some(e) = self.records.finish() {
+            return Some((self.line, Err(e)));
+        }
+        let started = self.records.start();
+        self.line = self.records.parser.pos.line();
+        match started {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some((self.line, Err(e))),
+        }
+        let item = self.records.root()?;
+        Some((self.line, item))
+    }
+}
+
+/// Reads an RFC 7464 JSON text sequence (`application/json-seq`), where each value
+/// is preceded by an ASCII record separator (0x1E), and returns them via `fn next()`.
+///
+/// Like [`JsonLines`], every error only ends the record it was found in.
+/// Empty records are ignored, and so is the lack of a separator before the first value.
+/// An array or object that is cut off by the next separator returns
+/// [`SyntaxError::EofWhileParsingList`] or [`SyntaxError::EofWhileParsingObject`].
+///
+/// As the RFC recommends, a top-level number, `true`, `false` or `null` that is not
+/// followed by whitespace might have been truncated, so it is returned as
+/// [`SyntaxError::EofWhileParsingValue`] instead.
+pub struct JsonSeq<R: BufRead> {
+    records: Records<R>,
+}
+
+impl<R: Read> JsonSeq<BufReader<R>> {
+    /// Constructs a new JsonSeq that will read from the provided object.
+    /// Reads are buffered internally, so `r` does not need to be buffered.
+    pub fn new(r: R) -> Self {
+        Self::from_buf_read(BufReader::new(r))
+    }
+}
+
+impl<R: BufRead> JsonSeq<R> {
+    /// Constructs a new JsonSeq that will read from the provided object,
+    /// using its buffer directly.
+    pub fn from_buf_read(r: R) -> Self {
+        Self {
+            records: Records::new(r, RS),
+        }
+    }
+
+    /// Replaces the default options
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.records.parser.options = options;
+        self
+    }
+
+    /// Returns the position of the next byte that will be read.
+    pub fn position(&self) -> Position {
+        self.records.parser.position()
+    }
+
+    /// Returns the value of the next record, or None when the input is exhausted.
+    ///
+    /// Anything left of the previous record is skipped first. If that record held more than
+    /// a single value, [`SyntaxError::TrailingCharacters`] is returned for it before moving on.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<'_>> {
+        if let Some(e) = self.records.finish() {
+            return Some(Err(e));
+        }
+        match self.records.start() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        match self.records.parser.peek_byte() {
+            Ok(Some(b)) if !starts_scalar(b) => return self.records.root(),
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+
+        let item = match self.records.root()? {
+            Ok(Json::Null) => Json::Null,
+            Ok(Json::Bool(b)) => Json::Bool(b),
+            Ok(Json::Number(n)) => Json::Number(n),
+            Ok(Json::RawNumber(n)) => Json::RawNumber(n),
+            Ok(_) => unreachable!("strings, arrays and objects were returned above"),
+            Err(e) => return Some(Err(e)),
+        };
+        let parser = &mut self.records.parser;
+        match parser.peek_byte() {
+            Ok(Some(_)) => Some(Ok(item)),
+            Ok(None) => {
+                self.records.poisoned = true;
+                let code = ErrorCode::Syntax(SyntaxError::EofWhileParsingValue);
+                Some(Err(Error::new(code, Some(parser.pos))))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Reads one record at a time, with the parser treating `delim` as the end of the input
+struct Records<R: BufRead> {
+    parser: Parser<R>,
+    delim: u8,
+    // a value was returned, and the rest of its record was not read yet
+    in_record: bool,
+    // the value of the current record was an error, so the rest of the record is not checked
+    poisoned: bool,
+}
+
+impl<R: BufRead> Records<R> {
+    fn new(r: R, delim: u8) -> Self {
+        let mut parser = Parser::from_buf_read(r);
+        parser.record_end = Some(delim);
+        Self {
+            parser,
+            delim,
+            in_record: false,
+            poisoned: false,
+        }
+    }
+
+    /// Moves past the record of the last value, returning an error
+    /// if reading failed, or if anything but whitespace followed the value.
+    fn finish(&mut self) -> Option<Error> {
+        if !std::mem::take(&mut self.in_record) {
+            return None;
+        }
+        // with a single root, this only returns errors
+        let trailing = if self.poisoned {
+            None
+        } else {
+            self.parser.next().and_then(|item| item.err())
+        };
+        match self.end_record() {
+            Ok(_) => trailing,
+            Err(e) => Some(e),
+        }
+    }
+
+    /// Moves to the first byte of the next value, past any empty records.
+    /// Returns false if the input ended first.
+    fn start(&mut self) -> Result<'static, bool> {
+        self.in_record = true;
+        self.poisoned = false;
+        loop {
+            if let Err(e) = (&mut self.parser as &mut dyn Parse).eat_whitespace() {
+                self.poisoned = true;
+                return Err(e);
+            }
+            if self.parser.peek_byte()?.is_some() {
+                return Ok(true);
+            }
+            if !self.end_record()? {
+                self.in_record = false;
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Returns the value at the start of the current record
+    fn root(&mut self) -> Option<Result<'_>> {
+        self.parser.roots = Roots::new(StreamMode::Single);
+        let poisoned = &mut self.poisoned;
+        Some(self.parser.next()?.inspect_err(|_| *poisoned = true))
+    }
+
+    /// Discards the rest of the current record, along with anything left to skip in it.
+    /// Returns false if the input ended instead of a delimiter.
+    fn end_record(&mut self) -> Result<'static, bool> {
+        let parser = &mut self.parser;
+        parser.skips.clear();
+        parser.depth = 0;
+        loop {
+            let n = parser.fill_buf()?.len();
+            if n == 0 {
+                break;
+            }
+            parser.consume(n);
+        }
+
+        parser.record_end = None;
+        let delim = parser.peek_byte().map(|b| b == Some(self.delim));
+        parser.record_end = Some(self.delim);
+        if delim? {
+            parser.consume_byte();
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
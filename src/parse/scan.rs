@@ -0,0 +1,782 @@
+//! State machines for the parts of the grammar that are read one byte at a time:
+//! numbers, the chars of strings, and skipping over input.
+//!
+//! They don't read anything themselves. Both [`Parser`](super::Parser) and the async parser
+//! feed them the bytes they have buffered, and consume as many as were used,
+//! so that both accept exactly the same input.
+
+use super::{
+    is_ident_byte, number_from_text, Delim, Number, NumberMode, ParserOptions, RawNumber,
+    SyntaxError,
+};
+
+/// Accumulates the digits of a hexadecimal integer
+#[derive(Debug)]
+pub(crate) struct HexScanner {
+    digits: usize,
+    // None after an overflow
+    value: Option<u128>,
+}
+
+impl HexScanner {
+    pub(crate) fn new() -> Self {
+        Self {
+            digits: 0,
+            value: Some(0),
+        }
+    }
+
+    pub(crate) fn push(&mut self, run: &[u8]) {
+        for &b in run {
+            let digit = hex_digit(b).unwrap();
+            self.value = self
+                .value
+                .and_then(|v| v.checked_mul(16))
+                .and_then(|v| v.checked_add(digit.into()));
+            self.digits += 1;
+        }
+    }
+
+    pub(crate) fn finish<J>(
+        &self,
+        negative: bool,
+        mode: NumberMode,
+        number: fn(Number) -> J,
+        raw: fn(RawNumber) -> J,
+    ) -> std::result::Result<J, SyntaxError> {
+        if self.digits == 0 {
+            return Err(SyntaxError::InvalidNumber);
+        }
+        let value = self.value.ok_or(SyntaxError::NumberOutOfRange)?;
+        let n = if negative {
+            let value = 0i128
+                .checked_sub_unsigned(value)
+                .ok_or(SyntaxError::NumberOutOfRange)?;
+            Number::from(value)
+        } else {
+            Number::from(value)
+        };
+        Ok(match mode {
+            NumberMode::Number => number(n),
+            NumberMode::Raw => raw(RawNumber(n.to_string())),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum NumState {
+    Minus,
+    Zero,
+    Int,
+    // a `.` without digits before it, only with relaxed numbers
+    LeadingDot,
+    Dot,
+    Frac,
+    Exp,
+    ExpSign,
+    ExpDigits,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum NumStep {
+    /// The byte was part of the number
+    More,
+    /// The byte follows a complete number
+    End,
+    /// The byte can't follow what was read so far
+    Invalid,
+}
+
+/// Numbers this long are rare enough to be spilled to the heap
+const NUMBER_INLINE_LEN: usize = 64;
+
+/// Checks a number against the RFC 8259 grammar one byte at a time,
+/// keeping its text for the final conversion.
+///
+/// With relaxed numbers, the text is kept as valid JSON: a leading `+` is dropped,
+/// and a `0` is added next to a decimal point without digits on that side.
+pub(crate) struct NumberScanner {
+    state: NumState,
+    relaxed: bool,
+    inline: [u8; NUMBER_INLINE_LEN],
+    len: usize,
+    spill: Vec<u8>,
+}
+
+impl NumberScanner {
+    /// Starts with the first byte of the number, which must be `-` or a digit,
+    /// or also `+` or `.` if `relaxed`
+    pub(crate) fn new(first: u8, relaxed: bool) -> Self {
+        let mut num = Self {
+            state: NumState::Minus,
+            relaxed,
+            inline: [0; NUMBER_INLINE_LEN],
+            len: 0,
+            spill: Vec::new(),
+        };
+        match first {
+            // a leading `+` is only kept as the state
+            b'+' => {}
+            b'-' => num.store(first),
+            _ => {
+                num.push(first);
+            }
+        }
+        num
+    }
+
+    pub(crate) fn push(&mut self, b: u8) -> NumStep {
+        use NumState::*;
+        let next = match (self.state, b) {
+            (Minus, b'0') => Zero,
+            (Minus, b'1'..=b'9') => Int,
+            (Minus, b'.') if self.relaxed => {
+                self.store(b'0');
+                LeadingDot
+            }
+            (Int, b'0'..=b'9') => Int,
+            (Zero | Int, b'.') => Dot,
+            (LeadingDot | Dot | Frac, b'0'..=b'9') => Frac,
+            (Dot, b'e' | b'E') if self.relaxed => {
+                self.store(b'0');
+                Exp
+            }
+            (Zero | Int | Frac, b'e' | b'E') => Exp,
+            (Exp, b'+' | b'-') => ExpSign,
+            (Exp | ExpSign | ExpDigits, b'0'..=b'9') => ExpDigits,
+            // like `01`, `1a` or `0x1`, which is only valid as a hexadecimal integer
+            _ if b.is_ascii_alphanumeric() => return NumStep::Invalid,
+            _ if self.is_complete() && !is_number_byte(b) => return NumStep::End,
+            _ => return NumStep::Invalid,
+        };
+        self.state = next;
+        self.store(b);
+        NumStep::More
+    }
+
+    /// Pushes the bytes of `buf` that are part of the number. Returns how many were,
+    /// and the step for the first one that wasn't, which is left in place.
+    /// An empty `buf` is the end of the input, which ends the number.
+    pub(crate) fn scan(&mut self, buf: &[u8]) -> (usize, Option<NumStep>) {
+        if buf.is_empty() {
+            return (0, Some(NumStep::End));
+        }
+        feed(buf, |b| match self.push(b) {
+            NumStep::More => Step::More,
+            step => Step::Before(step),
+        })
+    }
+
+    fn store(&mut self, b: u8) {
+        if self.len < NUMBER_INLINE_LEN {
+            self.inline[self.len] = b;
+            self.len += 1;
+        } else {
+            if self.spill.is_empty() {
+                self.spill.extend_from_slice(&self.inline);
+            }
+            self.spill.push(b);
+        }
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        match self.state {
+            NumState::Zero | NumState::Int | NumState::Frac | NumState::ExpDigits => true,
+            NumState::Dot => self.relaxed,
+            _ => false,
+        }
+    }
+
+    /// Returns true if only a sign was read, which could be followed by `Infinity` or `NaN`
+    pub(crate) fn is_sign(&self) -> bool {
+        self.state == NumState::Minus
+    }
+
+    /// Returns true if this is a `0` or `-0` that could be followed by the `x` of a hexadecimal integer
+    pub(crate) fn starts_hex(&self) -> bool {
+        matches!(self.text(), "0" | "-0")
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        let bytes = match self.spill.is_empty() {
+            true => &self.inline[..self.len],
+            false => &self.spill,
+        };
+        // the grammar only allows ascii
+        std::str::from_utf8(bytes).unwrap()
+    }
+
+    /// Converts the number read so far, failing if it was cut short.
+    /// `number` and `raw` build the value returned for each [`NumberMode`].
+    pub(crate) fn finish<J>(
+        &mut self,
+        mode: NumberMode,
+        number: fn(Number) -> J,
+        raw: fn(RawNumber) -> J,
+    ) -> std::result::Result<J, SyntaxError> {
+        if !self.is_complete() {
+            return Err(SyntaxError::InvalidNumber);
+        }
+        if self.state == NumState::Dot {
+            // like `5.`, which needs a digit after the decimal point to be valid JSON
+            self.store(b'0');
+        }
+        match mode {
+            NumberMode::Number => {
+                let is_integer = matches!(self.state, NumState::Zero | NumState::Int);
+                number_from_text(self.text(), is_integer).map(number)
+            }
+            NumberMode::Raw => Ok(raw(RawNumber(self.text().to_owned()))),
+        }
+    }
+}
+
+/// Bytes that can appear in a number. A number followed directly by one of them is invalid.
+fn is_number_byte(b: u8) -> bool {
+    matches!(b, b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+}
+
+fn hex_digit(b: u8) -> Option<u16> {
+    char::from(b).to_digit(16).map(|d| d as u16)
+}
+
+fn surrogate_pair(high: u16, low: u16) -> Option<char> {
+    if !(0xdc00..=0xdfff).contains(&low) {
+        return None;
+    }
+    let c = 0x10000 + ((u32::from(high) - 0xd800) << 10) + (u32::from(low) - 0xdc00);
+    char::from_u32(c)
+}
+
+/// Returns the length of a UTF-8 char starting with `lead`, if it's a valid first byte of one
+fn utf8_len(lead: u8) -> Option<usize> {
+    match lead {
+        0x00..=0x7f => Some(1),
+        0xc2..=0xdf => Some(2),
+        0xe0..=0xef => Some(3),
+        0xf0..=0xf4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Returns true for the bytes that JavaScript doesn't accept as an escaped char standing for itself:
+/// digits, the `x` and `u` of hex escapes, and line breaks
+fn is_js_escape_start(b: u8) -> bool {
+    matches!(b, b'0'..=b'9' | b'x' | b'u' | b'\n' | b'\r')
+}
+
+/// Returns true for the non-ASCII chars that JavaScript treats as line breaks
+fn is_js_line_break(c: char) -> bool {
+    matches!(c, '\u{2028}' | '\u{2029}')
+}
+
+/// What a byte fed to a `Skipper` or `CharDecoder` did
+#[derive(Debug)]
+enum Step<T> {
+    /// The byte was used, and more are needed
+    More,
+    /// The byte was used, and completed the result
+    Done(T),
+    /// The result was complete before the byte, which is left in place
+    Before(T),
+}
+
+/// Feeds `buf` to `push` until a result is complete.
+/// Returns how many bytes were used, and the result if there is one.
+fn feed<T>(buf: &[u8], mut push: impl FnMut(u8) -> Step<T>) -> (usize, Option<T>) {
+    for (i, &b) in buf.iter().enumerate() {
+        match push(b) {
+            Step::More => {}
+            Step::Done(t) => return (i + 1, Some(t)),
+            Step::Before(t) => return (i, Some(t)),
+        }
+    }
+    (buf.len(), None)
+}
+
+/// Skips over input without decoding it: dropped values and strings, comments, and malformed input.
+///
+/// Nesting is only counted, so this uses constant space for any input.
+#[derive(Debug)]
+pub(crate) struct Skipper {
+    until: Until,
+    /// Arrays and objects that were opened and not yet closed
+    depth: usize,
+    /// The bracket that opened the outermost of those, to report which one the input ended in
+    outer: u8,
+    token: Token,
+    single_quotes: bool,
+    comments: bool,
+}
+
+/// What ends a skip, once no string or comment is open
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Until {
+    /// The end of the string or comment it started in
+    Token,
+    /// The bracket closing the array or object it started in
+    Close,
+    /// A `,`, `]` or `}` outside of any array or object, which is left in place.
+    /// With `top_level`, whitespace also ends it, since that separates top-level values.
+    Entry { top_level: bool },
+}
+
+/// A string or comment being skipped
+#[derive(Debug, Copy, Clone)]
+enum Token {
+    None,
+    /// Ends at this quote, unless it follows a `\\`
+    Quoted {
+        quote: u8,
+        escape: bool,
+    },
+    /// An unquoted key, which ends before the first byte that can't be part of an identifier
+    Bare,
+    /// After a `/`, which only starts a comment if a `/` or `*` follows
+    Slash,
+    /// Ends before the next line feed
+    LineComment,
+    /// Ends after the next `*/`, `star` is set after a `*`
+    BlockComment {
+        star: bool,
+    },
+}
+
+impl Skipper {
+    fn new(until: Until, depth: usize, token: Token, options: &ParserOptions) -> Self {
+        Self {
+            until,
+            depth,
+            outer: b'[',
+            token,
+            single_quotes: options.allow_single_quotes,
+            comments: options.allow_comments,
+        }
+    }
+
+    /// Skips the rest of an array or object, after its opening bracket `open` was consumed
+    pub(crate) fn nested(open: u8, options: &ParserOptions) -> Self {
+        Self {
+            outer: open,
+            ..Self::new(Until::Close, 1, Token::None, options)
+        }
+    }
+
+    /// Skips the rest of a string, after its opening quote was consumed
+    pub(crate) fn string(delim: Delim, options: &ParserOptions) -> Self {
+        let token = match delim {
+            Delim::Quote(quote) => Token::Quoted {
+                quote,
+                escape: false,
+            },
+            Delim::Bare => Token::Bare,
+        };
+        Self::new(Until::Token, 0, token, options)
+    }
+
+    /// Skips the rest of a comment, after the `//` or `/*` starting it was consumed.
+    /// `kind` is the second byte of those.
+    ///
+    /// An unterminated block comment ends with the input.
+    pub(crate) fn comment(kind: u8, options: &ParserOptions) -> Self {
+        let token = match kind {
+            b'*' => Token::BlockComment { star: false },
+            _ => Token::LineComment,
+        };
+        Self::new(Until::Token, 0, token, options)
+    }
+
+    /// Skips the rest of an array item or object entry, up to the next `,`, `]` or `}` at the same depth
+    pub(crate) fn entry(top_level: bool, options: &ParserOptions) -> Self {
+        Self::new(Until::Entry { top_level }, 0, Token::None, options)
+    }
+
+    /// Skips what it can of `buf`. Returns how many bytes were used, and the result once the skip is done.
+    /// An empty `buf` is the end of the input, which ends any skip,
+    /// but is an error inside a string, array or object.
+    pub(crate) fn scan(
+        &mut self,
+        buf: &[u8],
+    ) -> (usize, Option<std::result::Result<(), SyntaxError>>) {
+        if buf.is_empty() {
+            return (0, Some(self.finish()));
+        }
+        let (n, done) = feed(buf, |b| self.push(b));
+        (n, done.map(Ok))
+    }
+
+    fn finish(&self) -> std::result::Result<(), SyntaxError> {
+        match self.token {
+            Token::Quoted { .. } => Err(SyntaxError::EofWhileParsingString),
+            _ if self.depth == 0 => Ok(()),
+            _ if self.outer == b'{' => Err(SyntaxError::EofWhileParsingObject),
+            _ => Err(SyntaxError::EofWhileParsingList),
+        }
+    }
+
+    fn push(&mut self, b: u8) -> Step<()> {
+        match self.token {
+            Token::None => self.push_outside(b),
+            Token::Quoted { quote, escape } => {
+                if b == quote && !escape {
+                    return self.token_end();
+                }
+                self.token = Token::Quoted {
+                    quote,
+                    escape: b == b'\\' && !escape,
+                };
+                Step::More
+            }
+            Token::Bare if is_ident_byte(b) => Step::More,
+            Token::Bare => Step::Before(()),
+            Token::Slash => {
+                self.token = match b {
+                    b'/' => Token::LineComment,
+                    b'*' => Token::BlockComment { star: false },
+                    // not a comment after all, so the byte is handled like any other
+                    _ => {
+                        self.token = Token::None;
+                        return self.push(b);
+                    }
+                };
+                Step::More
+            }
+            Token::LineComment if b == b'\n' => {
+                self.token = Token::None;
+                match self.until {
+                    Until::Token => Step::Before(()),
+                    _ => self.push(b),
+                }
+            }
+            Token::LineComment => Step::More,
+            Token::BlockComment { star } => {
+                if star && b == b'/' {
+                    return self.token_end();
+                }
+                self.token = Token::BlockComment { star: b == b'*' };
+                Step::More
+            }
+        }
+    }
+
+    /// Called after the last byte of a string or comment was used
+    fn token_end(&mut self) -> Step<()> {
+        self.token = Token::None;
+        match self.until {
+            Until::Token => Step::Done(()),
+            _ => Step::More,
+        }
+    }
+
+    /// Handles a byte that is not inside a string or comment
+    fn push_outside(&mut self, b: u8) -> Step<()> {
+        if let Until::Entry { top_level } = self.until {
+            let end = matches!(b, b',' | b']' | b'}') || (top_level && b.is_ascii_whitespace());
+            if end && self.depth == 0 {
+                return Step::Before(());
+            }
+        }
+        match b {
+            b'[' | b'{' => {
+                if self.depth == 0 {
+                    self.outer = b;
+                }
+                self.depth += 1;
+            }
+            b']' | b'}' => {
+                self.depth -= 1;
+                if self.depth == 0 && self.until == Until::Close {
+                    return Step::Done(());
+                }
+            }
+            b'"' => {
+                self.token = Token::Quoted {
+                    quote: b,
+                    escape: false,
+                }
+            }
+            b'\'' if self.single_quotes => {
+                self.token = Token::Quoted {
+                    quote: b,
+                    escape: false,
+                }
+            }
+            b'/' if self.comments => self.token = Token::Slash,
+            _ => {}
+        }
+        Step::More
+    }
+}
+
+/// Decodes the next char of a string, after its opening quote and any chars before were consumed.
+#[derive(Debug)]
+pub(crate) struct CharDecoder {
+    delim: Delim,
+    state: CharState,
+    single_quotes: bool,
+    js_escapes: bool,
+    control_characters: bool,
+}
+
+/// The result of a `CharDecoder`
+#[derive(Debug)]
+pub(crate) enum Decoded {
+    Char(char),
+    /// The closing quote was used, or an unquoted key ended
+    End,
+    Invalid(SyntaxError),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum CharState {
+    Start,
+    /// After a `\\`
+    Escape,
+    /// After `\\0`, which can't be followed by a digit
+    Zero,
+    /// After an escaped `\r`, which takes a following `\n` with it
+    CarriageReturn,
+    /// Reading the hex digits of an escape: their value so far, and how many are left
+    Hex {
+        kind: HexEscape,
+        n: u16,
+        left: u8,
+    },
+    /// After a leading surrogate, which must be followed by a `\\u` escape. `u` is set after its `\\`.
+    Surrogate {
+        high: u16,
+        u: bool,
+    },
+    /// Reading a multi-byte char. If it was `escaped`, a line break stands for nothing.
+    Utf8 {
+        bytes: [u8; 4],
+        len: usize,
+        read: usize,
+        escaped: bool,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+enum HexEscape {
+    /// `\\x`, with two digits
+    Byte,
+    /// `\\u`, with four
+    Unicode,
+    /// The `\\u` following this leading surrogate
+    Low(u16),
+}
+
+impl CharDecoder {
+    pub(crate) fn new(delim: Delim, options: &ParserOptions) -> Self {
+        Self {
+            delim,
+            state: CharState::Start,
+            single_quotes: options.allow_single_quotes,
+            js_escapes: options.allow_js_escapes,
+            control_characters: options.allow_control_characters,
+        }
+    }
+
+    /// Decodes what it can of `buf`. Returns how many bytes were used, and the result once there is one.
+    /// An empty `buf` is the end of the input.
+    ///
+    /// An escaped line break stands for nothing, so decoding goes on after it.
+    pub(crate) fn scan(&mut self, buf: &[u8]) -> (usize, Option<Decoded>) {
+        if buf.is_empty() {
+            return (0, Some(self.finish()));
+        }
+        feed(buf, |b| self.push(b))
+    }
+
+    fn finish(&self) -> Decoded {
+        match (self.delim, self.state) {
+            (Delim::Bare, _) => Decoded::End,
+            // the string is cut short after it, but this char is complete
+            (_, CharState::Zero) => Decoded::Char('\0'),
+            _ => Decoded::Invalid(SyntaxError::EofWhileParsingString),
+        }
+    }
+
+    fn push(&mut self, b: u8) -> Step<Decoded> {
+        match self.state {
+            CharState::Start => self.start(b),
+            CharState::Escape => self.escape(b),
+            // which would be an octal escape
+            CharState::Zero if b.is_ascii_digit() => {
+                Step::Before(Decoded::Invalid(SyntaxError::InvalidEscape))
+            }
+            CharState::Zero => Step::Before(Decoded::Char('\0')),
+            CharState::CarriageReturn => {
+                self.state = CharState::Start;
+                match b {
+                    b'\n' => Step::More,
+                    _ => self.start(b),
+                }
+            }
+            CharState::Hex { kind, n, left } => {
+                let Some(digit) = hex_digit(b) else {
+                    return Step::Before(Decoded::Invalid(SyntaxError::InvalidEscape));
+                };
+                let n = n * 16 + digit;
+                if left > 1 {
+                    self.state = CharState::Hex {
+                        kind,
+                        n,
+                        left: left - 1,
+                    };
+                    return Step::More;
+                }
+                self.hex_escape(kind, n)
+            }
+            CharState::Surrogate { high, u } => {
+                self.state = match (u, b) {
+                    (false, b'\\') => CharState::Surrogate { high, u: true },
+                    (true, b'u') => CharState::Hex {
+                        kind: HexEscape::Low(high),
+                        n: 0,
+                        left: 4,
+                    },
+                    _ => {
+                        return Step::Before(Decoded::Invalid(
+                            SyntaxError::UnexpectedEndOfHexEscape,
+                        ))
+                    }
+                };
+                Step::More
+            }
+            CharState::Utf8 {
+                mut bytes,
+                len,
+                read,
+                escaped,
+            } => {
+                if !(0x80..=0xbf).contains(&b) {
+                    return Step::Before(Decoded::Invalid(SyntaxError::InvalidUnicodeCodePoint));
+                }
+                bytes[read] = b;
+                if read + 1 < len {
+                    self.state = CharState::Utf8 {
+                        bytes,
+                        len,
+                        read: read + 1,
+                        escaped,
+                    };
+                    return Step::More;
+                }
+                let c = std::str::from_utf8(&bytes[..len])
+                    .ok()
+                    .and_then(|s| s.chars().next());
+                match c {
+                    Some(c) if escaped && is_js_line_break(c) => {
+                        self.state = CharState::Start;
+                        Step::More
+                    }
+                    Some(c) => Step::Done(Decoded::Char(c)),
+                    None => Step::Done(Decoded::Invalid(SyntaxError::InvalidUnicodeCodePoint)),
+                }
+            }
+        }
+    }
+
+    fn start(&mut self, b: u8) -> Step<Decoded> {
+        let quote = match self.delim {
+            Delim::Quote(q) => q,
+            Delim::Bare if is_ident_byte(b) => return Step::Done(Decoded::Char(b.into())),
+            Delim::Bare => return Step::Before(Decoded::End),
+        };
+        match b {
+            _ if b == quote => Step::Done(Decoded::End),
+            b'\\' => {
+                self.state = CharState::Escape;
+                Step::More
+            }
+            0x00..=0x1f if !self.control_characters => Step::Before(Decoded::Invalid(
+                SyntaxError::ControlCharacterWhileParsingString,
+            )),
+            0x00..=0x7f => Step::Done(Decoded::Char(b.into())),
+            _ => self.utf8(b, false),
+        }
+    }
+
+    /// Starts a multi-byte char with its first byte
+    fn utf8(&mut self, lead: u8, escaped: bool) -> Step<Decoded> {
+        match utf8_len(lead) {
+            Some(len) => {
+                self.state = CharState::Utf8 {
+                    bytes: [lead, 0, 0, 0],
+                    len,
+                    read: 1,
+                    escaped,
+                };
+                Step::More
+            }
+            None => Step::Before(Decoded::Invalid(SyntaxError::InvalidUnicodeCodePoint)),
+        }
+    }
+
+    /// Handles the byte following a `\\`
+    fn escape(&mut self, b: u8) -> Step<Decoded> {
+        let js = self.js_escapes;
+        self.state = match b {
+            _ if js && !b.is_ascii() => return self.utf8(b, true),
+            b'u' => CharState::Hex {
+                kind: HexEscape::Unicode,
+                n: 0,
+                left: 4,
+            },
+            b'x' if js => CharState::Hex {
+                kind: HexEscape::Byte,
+                n: 0,
+                left: 2,
+            },
+            b'0' if js => CharState::Zero,
+            // an escaped line break stands for nothing
+            b'\n' if js => CharState::Start,
+            b'\r' if js => CharState::CarriageReturn,
+            _ => {
+                return Step::Done(match self.simple_escape(b) {
+                    Some(c) => Decoded::Char(c),
+                    None => Decoded::Invalid(SyntaxError::InvalidEscape),
+                })
+            }
+        };
+        Step::More
+    }
+
+    /// Called with the value of a hex escape, after its last digit
+    fn hex_escape(&mut self, kind: HexEscape, n: u16) -> Step<Decoded> {
+        let lone = Decoded::Invalid(SyntaxError::LoneLeadingSurrogateInHexEscape);
+        Step::Done(match kind {
+            HexEscape::Byte => Decoded::Char(char::from(n as u8)),
+            HexEscape::Unicode => match char::from_u32(n.into()) {
+                Some(c) => Decoded::Char(c),
+                // a surrogate, which is only valid as the leading half of a pair
+                None if n < 0xdc00 => {
+                    self.state = CharState::Surrogate { high: n, u: false };
+                    return Step::More;
+                }
+                None => lone,
+            },
+            HexEscape::Low(high) => surrogate_pair(high, n).map_or(lone, Decoded::Char),
+        })
+    }
+
+    /// Decodes the escapes that are a single char after the `\\`
+    fn simple_escape(&self, b: u8) -> Option<char> {
+        Some(match b {
+            b'"' => '"',
+            b'\'' if self.single_quotes => '\'',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'v' if self.js_escapes => '\u{b}',
+            // other chars stand for themselves, except the ones that start longer escapes
+            b'\0'..=b'\x7f' if self.js_escapes && !is_js_escape_start(b) => b.into(),
+            _ => return None,
+        })
+    }
+}
@@ -185,3 +185,195 @@ fn emitter_newline_after_string() {
 "def""#
     );
 }
+
+#[test]
+fn parsed_numbers() {
+    use json_stream::parse::{Number, RawNumber};
+
+    let raw: RawNumber = "123456789012345678901234.000000001".parse().unwrap();
+    let mut buf = vec![];
+    {
+        let mut e = Emitter::new(&mut buf);
+        let mut arr = e.array().unwrap();
+        arr.emit(&raw);
+        arr.emit(&Number::from(-3));
+        arr.emit(&Number::from(2.0));
+    }
+
+    assert_eq!(
+        from_utf8(&buf).unwrap(),
+        "[123456789012345678901234.000000001,-3,2.0]"
+    );
+}
+
+#[test]
+fn non_finite_floats() {
+    use json_stream::parse::{JsonAccess, Number, Parser, ParserOptions};
+
+    let emit_all = |policy: NonFinite| {
+        let mut buf = vec![];
+        {
+            let mut e =
+                Emitter::new(&mut buf).with_options(EmitterOptions::new().non_finite(policy));
+            let mut arr = e.array().unwrap();
+            for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5] {
+                arr.emit(&x).unwrap();
+            }
+            arr.emit(&f32::NEG_INFINITY).unwrap();
+            let mut obj = arr.object().unwrap();
+            obj.emit("n", &Number::from(f64::INFINITY)).unwrap();
+        }
+        String::from_utf8(buf).unwrap()
+    };
+
+    assert_eq!(
+        emit_all(NonFinite::Null),
+        r#"[null,null,null,1.5,null,{"n":null}]"#
+    );
+    let literals = emit_all(NonFinite::Literal);
+    assert_eq!(
+        literals,
+        r#"[NaN,Infinity,-Infinity,1.5,-Infinity,{"n":Infinity}]"#
+    );
+
+    // the literals can be read back
+    let options = ParserOptions::new().allow_nan_and_infinity(true);
+    let mut p = Parser::from_str(&literals).with_options(options);
+    let mut arr = p.next().as_array().unwrap();
+    assert!(arr.next().as_number().unwrap().as_f64().is_nan());
+    assert_eq!(arr.next().as_number(), Some(Number::from(f64::INFINITY)));
+
+    let mut buf = vec![];
+    let mut e = Emitter::new(&mut buf);
+    let err = e.emit(&f64::NAN).unwrap_err();
+    assert!(err.is_non_finite_float());
+    assert!(!err.is_io());
+    e.emit(&Number::from(-0.5)).unwrap();
+    let err = e.emit(&Number::from(f64::NEG_INFINITY)).unwrap_err();
+    assert!(err.is_non_finite_float());
+}
+
+#[test]
+fn rejected_floats_leave_valid_json() {
+    use json_stream::parse::{Json, Parser};
+
+    let mut buf = vec![];
+    {
+        let mut e = Emitter::new(&mut buf);
+        e.emit(&1);
+        assert!(e.emit(&f64::NAN).is_err());
+        e.emit(&2);
+        assert!(e.emit(&vec![1.0, f64::NAN]).is_err());
+        let mut arr = e.array().unwrap();
+        arr.emit(&1);
+        assert!(arr.emit(&f64::NAN).is_err());
+        assert!(arr.emit(&[f64::INFINITY]).is_err());
+        arr.emit(&2);
+        let mut obj = arr.object().unwrap();
+        assert!(obj.emit("x", &f32::NAN).is_err());
+        assert!(obj.emit("m", &HashMap::from([("a", f64::NAN)])).is_err());
+        obj.emit("y", &1);
+    }
+    assert_eq!(from_utf8(&buf).unwrap(), "1\n2\n[1,2,{\"y\":1}]");
+
+    fn check_valid(item: Json<'_>) {
+        match item {
+            Json::Array(mut arr) => {
+                while let Some(item) = arr.next() {
+                    check_valid(item.unwrap());
+                }
+            }
+            Json::Object(mut obj) => {
+                while let Some(kv) = obj.next() {
+                    check_valid(kv.unwrap().value().unwrap());
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut p = Parser::new(&buf[..]);
+    while let Some(item) = p.next() {
+        check_valid(item.unwrap());
+    }
+}
+
+#[test]
+fn record_separators() {
+    let mut buf = vec![];
+    {
+        let options = EmitterOptions::new().separator(Separator::RecordSeparator);
+        let mut e = Emitter::new(&mut buf).with_options(options);
+        e.string().unwrap().str("a");
+        e.emit(&[1, 2]);
+        e.array().unwrap().array().unwrap().emit(&3);
+        e.flush().unwrap();
+        e.emit(&4);
+    }
+    // each record is ended right after its value, even without a flush
+    assert_eq!(buf, b"\x1e\"a\"\n\x1e[1,2]\n\x1e[[3]]\n\x1e4\n");
+
+    // nothing is written without values
+    let mut buf = vec![];
+    let options = EmitterOptions::new().separator(Separator::RecordSeparator);
+    Emitter::new(&mut buf)
+        .with_options(options)
+        .flush()
+        .unwrap();
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn base64_strings() {
+    use json_stream::base64::Alphabet;
+    use json_stream::parse::{JsonAccess, Parser};
+    use std::io::{Read, Write};
+
+    let mut buf = vec![];
+    {
+        let mut e = Emitter::new(&mut buf);
+        let mut arr = e.array().unwrap();
+        for (bytes, alphabet) in [
+            (&b""[..], Alphabet::Standard),
+            (b"f", Alphabet::Standard),
+            (b"fo", Alphabet::Standard),
+            (b"foo", Alphabet::Standard),
+            (&[0xfb, 0xff, 0xbf, 0xfb], Alphabet::Standard),
+            (&[0xfb, 0xff, 0xbf, 0xfb], Alphabet::UrlSafe),
+        ] {
+            let mut s = arr.string().unwrap();
+            let mut w = s.base64(alphabet);
+            // split across writes
+            for chunk in bytes.chunks(2) {
+                w.write_all(chunk).unwrap();
+            }
+            w.finish().unwrap();
+        }
+        // padded when dropped
+        arr.string()
+            .unwrap()
+            .base64(Alphabet::Standard)
+            .write_all(b"foob");
+    }
+    assert_eq!(
+        from_utf8(&buf).unwrap(),
+        r#"["","Zg==","Zm8=","Zm9v","+/+/+w==","-_-_-w==","Zm9vYg=="]"#
+    );
+
+    // large writes round trip
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 256) as u8).collect();
+    let mut buf = vec![];
+    Emitter::new(&mut buf)
+        .string()
+        .unwrap()
+        .base64(Alphabet::UrlSafe)
+        .write_all(&data);
+    let mut p = Parser::new(&buf[..]);
+    let mut decoded = vec![];
+    p.next()
+        .as_string()
+        .unwrap()
+        .read_base64(Alphabet::UrlSafe)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, data);
+}
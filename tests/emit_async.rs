@@ -0,0 +1,141 @@
+#![cfg(feature = "async")]
+
+use json_stream::emit::*;
+use std::str::from_utf8;
+
+#[tokio::test]
+async fn example() {
+    let mut buf = vec![];
+    {
+        let mut e = AsyncEmitter::new(&mut buf);
+
+        let mut arr = e.array().await.unwrap();
+        arr.emit("a").await.unwrap();
+        {
+            let mut obj = arr.object().await.unwrap();
+            obj.emit("k", "v").await.unwrap();
+            obj.end().await.unwrap();
+        }
+        arr.emit(&3).await.unwrap();
+        arr.end().await.unwrap();
+    }
+
+    assert_eq!(from_utf8(&buf).unwrap(), r#"["a",{"k":"v"},3]"#);
+}
+
+#[tokio::test]
+async fn commas_in_object() {
+    let mut buf = vec![];
+    {
+        let mut e = AsyncEmitter::new(&mut buf);
+
+        let mut o = e.object().await.unwrap();
+        o.emit("a", &1).await.unwrap();
+        o.emit_array("b").await.unwrap().end().await.unwrap();
+        let mut c = o.emit_object("c").await.unwrap();
+        c.emit("d", &[1, 2]).await.unwrap();
+        c.end().await.unwrap();
+        o.end().await.unwrap();
+    }
+
+    assert_eq!(
+        from_utf8(&buf).unwrap(),
+        r#"{"a":1,"b":[],"c":{"d":[1,2]}}"#
+    );
+}
+
+#[tokio::test]
+async fn strings() {
+    let mut buf = vec![];
+    {
+        let mut e = AsyncEmitter::new(&mut buf);
+        let mut s = e.string().await.unwrap();
+        s.char('a').await.unwrap();
+        s.str("bcd").await.unwrap();
+        s.end().await.unwrap();
+
+        e.emit("efg").await.unwrap();
+    }
+
+    assert_eq!(
+        from_utf8(&buf).unwrap(),
+        r#""abcd"
+"efg""#
+    );
+}
+
+#[tokio::test]
+async fn dropped_emitters_are_closed_lazily() {
+    let mut buf = vec![];
+    {
+        let mut e = AsyncEmitter::new(&mut buf);
+        {
+            let mut arr = e.array().await.unwrap();
+            let mut obj = arr.object().await.unwrap();
+            obj.emit("x", &5).await.unwrap();
+        }
+        e.emit(&3).await.unwrap();
+        e.array().await.unwrap();
+        e.flush().await.unwrap();
+    }
+
+    assert_eq!(
+        from_utf8(&buf).unwrap(),
+        r#"[{"x":5}]
+3
+[]"#
+    );
+}
+
+#[test]
+fn futures_are_send() {
+    fn assert_send<T: Send>(_: T) {}
+
+    let mut e = AsyncEmitter::new(Vec::new());
+    assert_send(e.emit(&1));
+}
+
+#[tokio::test]
+async fn non_finite_floats() {
+    let mut buf = vec![];
+    {
+        let options = EmitterOptions::new().non_finite(NonFinite::Literal);
+        let mut e = AsyncEmitter::new(&mut buf).with_options(options);
+        let mut arr = e.array().await.unwrap();
+        arr.emit(&f64::NAN).await.unwrap();
+        arr.emit(&[f32::INFINITY]).await.unwrap();
+        arr.end().await.unwrap();
+    }
+    assert_eq!(from_utf8(&buf).unwrap(), "[NaN,[Infinity]]");
+
+    let mut buf = vec![];
+    let mut e = AsyncEmitter::new(&mut buf);
+    let mut arr = e.array().await.unwrap();
+    assert!(arr
+        .emit(&f64::INFINITY)
+        .await
+        .unwrap_err()
+        .is_non_finite_float());
+    arr.emit(&1).await.unwrap();
+    arr.end().await.unwrap();
+    // nothing is written for a value that cannot be emitted
+    assert_eq!(from_utf8(&buf).unwrap(), "[1]");
+}
+
+#[tokio::test]
+async fn record_separators() {
+    let mut buf = vec![];
+    {
+        let options = EmitterOptions::new().separator(Separator::RecordSeparator);
+        let mut e = AsyncEmitter::new(&mut buf).with_options(options);
+        e.emit(&1).await.unwrap();
+        e.object().await.unwrap().emit("a", &[2]).await.unwrap();
+        e.flush().await.unwrap();
+        e.emit("three").await.unwrap();
+        let mut arr = e.array().await.unwrap();
+        arr.array().await.unwrap().end().await.unwrap();
+        arr.end().await.unwrap();
+    }
+    // each record is ended right after its value, even without a flush
+    assert_eq!(buf, b"\x1e1\n\x1e{\"a\":[2]}\n\x1e\"three\"\n\x1e[[]]\n");
+}
@@ -29,7 +29,7 @@ fn chars() {
         .as_string()
         .expect("expected root value to be a string");
 
-    let chars: Vec<char> = s.read_chars().collect();
+    let chars: Vec<char> = s.read_chars().collect::<Result<_, _>>().unwrap();
 
     assert_eq!(chars, &['a', 'b', 'c']);
 }
@@ -43,7 +43,7 @@ fn chars_into_string() {
         .as_string()
         .expect("expected root value to be a string");
 
-    let chars: String = s.read_chars().collect();
+    let chars: String = s.read_chars().collect::<Result<_, _>>().unwrap();
 
     assert_eq!(chars, "abc");
 }
@@ -71,7 +71,7 @@ fn test_single_char(expected: char, s: &str) {
         .as_string()
         .expect("expected root value to be a string");
 
-    let chars: Vec<char> = str_parser.read_chars().into_iter().collect();
+    let chars: Vec<char> = str_parser.read_chars().collect::<Result<_, _>>().unwrap();
 
     dbg!(&chars, s, expected, expected.escape_unicode().to_string());
     assert_eq!(chars, &[expected]);
@@ -83,9 +83,17 @@ fn char_escapes() {
         ('\\', r#"\\"#),
         ('"', r#"\""#),
         ('\r', r#"\r"#),
-        ('\u{1234}', r#"\u{1234}"#),
-        ('\u{ab34}', r#"\u{ab34}"#),
-        ('\u{AB34}', r#"\u{ab34}"#),
+        ('\n', r#"\n"#),
+        ('\t', r#"\t"#),
+        ('/', r#"\/"#),
+        ('\u{8}', r#"\b"#),
+        ('\u{c}', r#"\f"#),
+        ('\u{1234}', r#"\u1234"#),
+        ('\u{ab34}', r#"\uab34"#),
+        ('\u{AB34}', r#"\uAB34"#),
+        ('\u{1f600}', r#"\ud83d\ude00"#),
+        ('é', "é"),
+        ('😀', "😀"),
     ];
 
     for (c, s) in &pairs {
@@ -94,6 +102,7 @@ fn char_escapes() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn basics() {
     let mut p = Parser::new("null true false 0 1 -2 6.28".as_bytes());
 
@@ -130,14 +139,14 @@ fn object_and_keyval() {
     };
 
     let mut kv = obj.next().unwrap().unwrap();
-    assert_eq!(kv.key().read_owned(), Ok("a".to_owned()));
+    assert_eq!(kv.key().unwrap().read_owned(), Ok("a".to_owned()));
     assert_eq!(kv.value().as_number(), Some(Number::from(2)));
 
     let kv = obj.next().unwrap().unwrap();
     assert!(kv.value().is_array());
 
     let mut kv = obj.next().unwrap().unwrap();
-    assert_eq!(kv.key().read_owned(), Ok("c".to_owned()));
+    assert_eq!(kv.key().unwrap().read_owned(), Ok("c".to_owned()));
     drop(kv);
 
     assert!(obj.next().is_none());
@@ -153,7 +162,7 @@ fn object_skipping() {
         .expect("expected root value to be an object");
 
     let mut kv = obj.next().unwrap().unwrap();
-    assert_eq!(kv.key().read_owned(), Ok("a".to_owned()));
+    assert_eq!(kv.key().unwrap().read_owned(), Ok("a".to_owned()));
     drop(kv);
 
     let kv = obj.next().unwrap().unwrap();
@@ -252,3 +261,2263 @@ mod identifier_errors {
         assert_eq!(p.next().unwrap().as_bool(), Some(false));
     }
 }
+
+mod io_errors {
+    use super::*;
+    use std::io::{self, ErrorKind, Read};
+
+    /// Yields the wrapped bytes, then fails every read after them
+    struct FailAfter<'a>(&'a [u8]);
+
+    impl Read for FailAfter<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::new(
+                    ErrorKind::ConnectionReset,
+                    "connection reset",
+                ));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn error_at_root() {
+        let mut p = Parser::new(FailAfter(b"1 "));
+
+        assert_eq!(p.next().as_number(), Some(Number::from(1)));
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.io().map(io::Error::kind),
+            Some(ErrorKind::ConnectionReset)
+        );
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn error_inside_subparsers() {
+        let mut p = Parser::new(FailAfter(br#"[1, {"a": "bc"#));
+
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        let mut obj = arr.next().as_object().unwrap();
+        let kv = obj.next().unwrap().unwrap();
+        let s = kv.value().as_string().unwrap();
+        assert!(s.read_owned().unwrap_err().is_io());
+
+        assert!(obj.next().is_none());
+        drop(obj);
+        assert!(arr.next().is_none());
+        drop(arr);
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn error_while_skipping() {
+        let mut p = Parser::new(FailAfter(br#"[[1, 2"#));
+
+        let mut arr = p.next().as_array().unwrap();
+        drop(arr.next());
+        assert!(arr.next().unwrap().unwrap_err().is_io());
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn error_in_chars() {
+        let mut p = Parser::new(FailAfter(br#""ab"#));
+
+        let chars: Vec<_> = p.next().as_string().unwrap().read_chars().collect();
+        assert_eq!(chars[..2], [Ok('a'), Ok('b')]);
+        assert!(chars[2].as_ref().unwrap_err().is_io());
+        assert_eq!(chars.len(), 3);
+    }
+}
+
+mod positions {
+    use super::*;
+
+    #[test]
+    fn parser_position() {
+        let mut p = Parser::new("[1,\n\"é\"]\n 7".as_bytes());
+
+        let pos = p.position();
+        assert_eq!((pos.offset(), pos.line(), pos.column()), (0, 1, 1));
+
+        {
+            let mut arr = p.next().as_array().unwrap();
+            arr.next();
+            arr.next().as_string().unwrap().read_owned().unwrap();
+        }
+        let pos = p.position();
+        assert_eq!((pos.offset(), pos.line(), pos.column()), (8, 2, 4));
+
+        p.next();
+        let pos = p.position();
+        assert_eq!((pos.offset(), pos.line(), pos.column()), (12, 3, 3));
+    }
+
+    #[test]
+    fn error_position() {
+        let mut p = Parser::new("[1,\n  2 3]".as_bytes());
+
+        let mut arr = p.next().as_array().unwrap();
+        arr.next();
+        arr.next();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::MissingComma));
+
+        let pos = err.position().unwrap();
+        assert_eq!((pos.offset(), pos.line(), pos.column()), (8, 2, 5));
+        assert_eq!(
+            err.to_string(),
+            "syntax error: MissingComma at line 2 column 5"
+        );
+    }
+
+    #[test]
+    fn invalid_ident_position() {
+        let mut p = Parser::new("null\n nope".as_bytes());
+
+        p.next();
+        let pos = p.next().unwrap().unwrap_err().position().unwrap();
+        assert_eq!((pos.line(), pos.column()), (2, 4));
+    }
+}
+
+mod buffering {
+    use super::*;
+    use std::io::BufReader;
+
+    const DOC: &str = r#"{"skipped": [1, {"x": "]}\""}], "s": "a\"bc", "n": -12.5e1, "t": true} 7"#;
+
+    fn read_doc<R: std::io::BufRead>(mut p: Parser<R>) -> (String, Number, bool, Number) {
+        let (s, n, t) = {
+            let mut obj = p.next().as_object().unwrap();
+            drop(obj.next());
+            let s = obj
+                .next()
+                .unwrap()
+                .unwrap()
+                .value()
+                .as_string()
+                .unwrap()
+                .read_owned()
+                .unwrap();
+            let n = obj.next().unwrap().unwrap().value().as_number().unwrap();
+            let t = obj.next().unwrap().unwrap().value().as_bool().unwrap();
+            assert!(obj.next().is_none());
+            (s, n, t)
+        };
+        let last = p.next().as_number().unwrap();
+        assert_eq!(p.position().offset(), DOC.len() as u64);
+        assert!(p.next().is_none());
+        (s, n, t, last)
+    }
+
+    #[test]
+    fn any_buffer_size() {
+        let expected = read_doc(Parser::new(DOC.as_bytes()));
+        assert_eq!(
+            expected,
+            (
+                "a\"bc".to_owned(),
+                Number::from(-125.0),
+                true,
+                Number::from(7)
+            )
+        );
+
+        for capacity in 1..=16 {
+            let p = Parser::from_buf_read(BufReader::with_capacity(capacity, DOC.as_bytes()));
+            assert_eq!(read_doc(p), expected, "capacity {}", capacity);
+        }
+    }
+}
+
+mod zero_copy {
+    use super::*;
+    use std::borrow::Cow;
+    use std::io::BufReader;
+
+    #[test]
+    fn borrowed_without_escapes() {
+        let mut p = Parser::from_str(r#"["abc", "d\"e", "é"] "f""#);
+
+        {
+            let mut arr = p.next().as_array().unwrap();
+
+            let s = arr.next().as_string().unwrap().read_cow().unwrap();
+            assert!(matches!(s, Cow::Borrowed("abc")));
+
+            let s = arr.next().as_string().unwrap().read_cow().unwrap();
+            assert!(matches!(s, Cow::Owned(_)));
+            assert_eq!(s, "d\"e");
+
+            let s = arr.next().as_string().unwrap().read_cow().unwrap();
+            assert!(matches!(s, Cow::Borrowed("é")));
+
+            assert!(arr.next().is_none());
+        }
+
+        let s = p.next().as_string().unwrap().read_cow().unwrap();
+        assert!(matches!(s, Cow::Borrowed("f")));
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn borrowed_key_then_value() {
+        let mut p = Parser::from_slice(br#"{"key": "value", "k2": 2}"#);
+
+        let mut obj = p.next().as_object().unwrap();
+        let mut kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_cow().unwrap(), "key");
+        assert_eq!(kv.value().as_string().unwrap().read_cow().unwrap(), "value");
+
+        let mut kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_cow().unwrap(), "k2");
+        assert_eq!(kv.value().as_number(), Some(Number::from(2)));
+        assert!(obj.next().is_none());
+    }
+
+    #[test]
+    fn falls_back_when_not_buffered() {
+        let src = BufReader::with_capacity(4, r#"["abcdefgh", "ij"]"#.as_bytes());
+        let mut p = Parser::from_buf_read(src);
+
+        let mut arr = p.next().as_array().unwrap();
+        let s = arr.next().as_string().unwrap().read_cow().unwrap();
+        assert!(matches!(s, Cow::Owned(_)));
+        assert_eq!(s, "abcdefgh");
+        assert_eq!(arr.next().as_string().unwrap().read_cow().unwrap(), "ij");
+        assert!(arr.next().is_none());
+    }
+}
+
+mod escapes {
+    use super::*;
+    use std::io::BufReader;
+
+    fn read(s: &str) -> Result<String, Error> {
+        Parser::new(s.as_bytes())
+            .next()
+            .as_string()
+            .unwrap()
+            .read_owned()
+    }
+
+    #[test]
+    fn standard_escapes() {
+        assert_eq!(
+            read(r#""\"\\\/\b\f\n\r\t""#),
+            Ok("\"\\/\u{8}\u{c}\n\r\t".to_owned())
+        );
+        assert_eq!(read(r#""café é""#), Ok("café é".to_owned()));
+        assert_eq!(read(r#""😀!""#), Ok("😀!".to_owned()));
+        assert_eq!(read(r#""é😀""#), Ok("é😀".to_owned()));
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let cases = [
+            (r#""\x""#, SyntaxError::InvalidEscape),
+            (r#""\u12g4""#, SyntaxError::InvalidEscape),
+            (r#""\ude00""#, SyntaxError::LoneLeadingSurrogateInHexEscape),
+            (
+                r#""\ud83d\u0041""#,
+                SyntaxError::LoneLeadingSurrogateInHexEscape,
+            ),
+            (r#""\ud83d""#, SyntaxError::UnexpectedEndOfHexEscape),
+            (r#""\ud83dx""#, SyntaxError::UnexpectedEndOfHexEscape),
+            (r#""\u12"#, SyntaxError::EofWhileParsingString),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(read(s).unwrap_err().syntax(), Some(expected), "{}", s);
+        }
+
+        let mut p = Parser::from_slice(b"\"\xff\"");
+        let err = p.next().as_string().unwrap().read_owned().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidUnicodeCodePoint));
+    }
+
+    #[test]
+    fn error_leaves_buffer_and_skips_string() {
+        let mut p = Parser::new(r#"["a\qb", "c"]"#.as_bytes());
+
+        let mut arr = p.next().as_array().unwrap();
+        let mut buf = "kept".to_owned();
+        let err = arr
+            .next()
+            .as_string()
+            .unwrap()
+            .read_into(&mut buf)
+            .unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidEscape));
+        assert_eq!(buf, "kept");
+
+        assert_eq!(
+            arr.next().as_string().unwrap().read_owned(),
+            Ok("c".to_owned())
+        );
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn chars_errors() {
+        let mut p = Parser::new(r#"["a\u{1234}b", "c"]"#.as_bytes());
+
+        let mut arr = p.next().as_array().unwrap();
+        let chars: Vec<_> = arr.next().as_string().unwrap().read_chars().collect();
+        assert_eq!(chars[0], Ok('a'));
+        assert_eq!(
+            chars[1].as_ref().unwrap_err().syntax(),
+            Some(SyntaxError::InvalidEscape)
+        );
+        assert_eq!(chars.len(), 2);
+
+        assert_eq!(
+            arr.next().as_string().unwrap().read_owned(),
+            Ok("c".to_owned())
+        );
+    }
+
+    #[test]
+    fn chars_eof() {
+        let mut p = Parser::new(r#""ab"#.as_bytes());
+
+        let chars: Vec<_> = p.next().as_string().unwrap().read_chars().collect();
+        assert_eq!(chars[..2], [Ok('a'), Ok('b')]);
+        assert_eq!(
+            chars[2].as_ref().unwrap_err().syntax(),
+            Some(SyntaxError::EofWhileParsingString)
+        );
+    }
+
+    #[test]
+    fn chars_dropped_early() {
+        let mut p = Parser::new(r#"["a\"é", 1]"#.as_bytes());
+
+        let mut arr = p.next().as_array().unwrap();
+        let mut chars = arr.next().as_string().unwrap().read_chars();
+        assert_eq!(chars.next(), Some(Ok('a')));
+        drop(chars);
+
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+    }
+
+    #[test]
+    fn split_across_refills() {
+        let json = r#""aé😀é😀z""#;
+        for capacity in 1..json.len() {
+            let mut p = Parser::from_buf_read(BufReader::with_capacity(capacity, json.as_bytes()));
+            let s = p.next().as_string().unwrap().read_owned();
+            assert_eq!(s, Ok("aé😀é😀z".to_owned()), "capacity {}", capacity);
+
+            let mut p = Parser::from_buf_read(BufReader::with_capacity(capacity, json.as_bytes()));
+            let s: Result<String, _> = p.next().as_string().unwrap().read_chars().collect();
+            assert_eq!(s, Ok("aé😀é😀z".to_owned()), "capacity {}", capacity);
+        }
+    }
+}
+
+mod numbers {
+    use super::*;
+    use std::io::BufReader;
+
+    fn parse(s: &str) -> Result<Number, Option<SyntaxError>> {
+        match Parser::new(s.as_bytes()).next() {
+            Some(Ok(Json::Number(n))) => Ok(n),
+            Some(Err(e)) => Err(e.syntax()),
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        let cases = [
+            ("0", Number::from(0)),
+            ("-0", Number::from(0)),
+            ("12", Number::from(12)),
+            ("-12", Number::from(-12)),
+            ("18446744073709551615", Number::from(u64::MAX)),
+            ("-9223372036854775808", Number::from(i64::MIN)),
+            ("0.5", Number::from(0.5)),
+            ("-0.25", Number::from(-0.25)),
+            ("1e3", Number::from(1000.0)),
+            ("1E+3", Number::from(1000.0)),
+            ("25e-2", Number::from(0.25)),
+            ("1.5e1", Number::from(15.0)),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(parse(s), Ok(expected), "{}", s);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let cases = [
+            "012", "-01", "1.", "1.e5", "-", "-a", "1e", "1e+", "1-2", "1.2.3", "1e5e5", "1+2",
+            "--1", ".5", "+1", "0x1", "-0x1", "0a", "0e", "00", "1a", "1.5x", "1e3z", "12null",
+        ];
+        for s in cases {
+            assert_eq!(parse(s), Err(Some(SyntaxError::InvalidNumber)), "{}", s);
+        }
+        assert_eq!(parse("1e400"), Err(Some(SyntaxError::NumberOutOfRange)));
+    }
+
+    #[test]
+    fn error_position_and_recovery() {
+        let mut p = Parser::new("[012, 3]".as_bytes());
+
+        let mut arr = p.next().as_array().unwrap();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        assert_eq!(err.position().unwrap().offset(), 2);
+
+        assert_eq!(arr.next().as_number(), Some(Number::from(3)));
+        assert!(arr.next().is_none());
+        drop(arr);
+
+        // hexadecimal needs the relaxed syntax
+        let mut p = Parser::new("[0x1F, 4]".as_bytes());
+        let mut arr = p.next().as_array().unwrap();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        assert_eq!(err.position().unwrap().offset(), 2);
+        assert_eq!(arr.next().as_number(), Some(Number::from(4)));
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn long_and_split_across_refills() {
+        let long = format!("0.{}1", "0".repeat(100));
+        let json = format!("[-1234.5e-2, {}, 12345678901234567890]", long);
+        let expected = [
+            Number::from(-12.345),
+            Number::from(long.parse::<f64>().unwrap()),
+            Number::from(12345678901234567890u64),
+        ];
+        for capacity in 1..20 {
+            let mut p = Parser::from_buf_read(BufReader::with_capacity(capacity, json.as_bytes()));
+            let mut arr = p.next().as_array().unwrap();
+            for n in expected {
+                assert_eq!(arr.next().as_number(), Some(n), "capacity {}", capacity);
+            }
+            assert!(arr.next().is_none());
+        }
+    }
+}
+
+mod number_values {
+    use super::*;
+
+    #[test]
+    fn accessors() {
+        let n = Number::from(300u32);
+        assert!(n.is_integer() && n.is_u64() && n.is_i64() && !n.is_f64());
+        assert_eq!(
+            (n.as_u64(), n.as_i64(), n.as_f64()),
+            (Some(300), Some(300), 300.0)
+        );
+
+        let n = Number::from(-5);
+        assert!(n.is_integer() && !n.is_u64() && n.is_i64());
+        assert_eq!((n.as_u64(), n.as_i64(), n.as_f64()), (None, Some(-5), -5.0));
+
+        let n = Number::from(u64::MAX);
+        assert_eq!((n.as_u64(), n.as_i64()), (Some(u64::MAX), None));
+
+        let n = Number::from(2.0);
+        assert!(!n.is_integer() && n.is_f64());
+        assert_eq!((n.as_u64(), n.as_i64(), f64::from(n)), (None, None, 2.0));
+    }
+
+    #[test]
+    fn checked_conversions() {
+        let out_of_range = Some(SyntaxError::NumberOutOfRange);
+
+        assert_eq!(u8::try_from(Number::from(255)).ok(), Some(255));
+        assert_eq!(
+            u8::try_from(Number::from(256)).unwrap_err().syntax(),
+            out_of_range
+        );
+        assert_eq!(
+            u32::try_from(Number::from(-1)).unwrap_err().syntax(),
+            out_of_range
+        );
+        assert_eq!(i8::try_from(Number::from(-128)).ok(), Some(-128));
+        assert_eq!(
+            i8::try_from(Number::from(128)).unwrap_err().syntax(),
+            out_of_range
+        );
+        assert_eq!(
+            i64::try_from(Number::from(u64::MAX)).unwrap_err().syntax(),
+            out_of_range
+        );
+        assert_eq!(usize::try_from(Number::from(7)).ok(), Some(7));
+        assert_eq!(
+            i32::try_from(Number::from(1.0)).unwrap_err().syntax(),
+            out_of_range
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut p = Parser::new("0 -12 18446744073709551615 0.5 1e300 2.0 -1.5e-7".as_bytes());
+        let mut seen = vec![];
+        while let Some(n) = p.next().as_number() {
+            seen.push(n.to_string());
+        }
+        assert_eq!(
+            seen,
+            [
+                "0",
+                "-12",
+                "18446744073709551615",
+                "0.5",
+                "1e300",
+                "2.0",
+                "-1.5e-7"
+            ]
+        );
+    }
+
+    #[test]
+    fn ordering_across_representations() {
+        assert_eq!(Number::from(1), Number::from(1.0));
+        assert_eq!(Number::from(-3), Number::from(-3.0));
+        assert!(Number::from(1) < Number::from(1.5));
+        assert!(Number::from(-1) > Number::from(-1.5));
+        assert!(Number::from(-1) < Number::from(0u8));
+        assert!(Number::from(u64::MAX) > Number::from(1e19));
+        assert!(Number::from(u64::MAX) < Number::from(1.9e19));
+        assert!(Number::from(i64::MIN) > Number::from(-1e300));
+        // u64::MAX rounds up to this float, but isn't equal to it
+        assert!(Number::from(u64::MAX) < Number::from(u64::MAX as f64));
+        assert_eq!(Number::from(f64::NAN).partial_cmp(&Number::from(1)), None);
+    }
+}
+
+mod raw_numbers {
+    use super::*;
+
+    #[test]
+    fn keeps_exact_text() {
+        let mut p = Parser::from_str("[123456789012345678901234.000000001, -0, 1E+2, 7]")
+            .with_options(ParserOptions::new().number_mode(NumberMode::Raw));
+
+        let mut arr = p.next().as_array().unwrap();
+        let mut seen = vec![];
+        while let Some(n) = arr.next().as_raw_number() {
+            seen.push(n.into_string());
+        }
+        assert_eq!(
+            seen,
+            ["123456789012345678901234.000000001", "-0", "1E+2", "7"]
+        );
+    }
+
+    #[test]
+    fn grammar_still_checked() {
+        let mut p =
+            Parser::from_str("012").with_options(ParserOptions::new().number_mode(NumberMode::Raw));
+        assert_eq!(
+            p.next().unwrap().unwrap_err().syntax(),
+            Some(SyntaxError::InvalidNumber)
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        let n: RawNumber = "300".parse().unwrap();
+        assert!(n.is_integer());
+        assert_eq!(u16::try_from(&n).ok(), Some(300));
+        assert_eq!(
+            u8::try_from(&n).unwrap_err().syntax(),
+            Some(SyntaxError::NumberOutOfRange)
+        );
+        assert_eq!(n.to_number().ok(), Some(Number::from(300)));
+
+        let n: RawNumber = "-1.5e3".parse().unwrap();
+        assert!(!n.is_integer());
+        assert_eq!(f64::try_from(&n).ok(), Some(-1500.0));
+        assert!(i64::try_from(&n).is_err());
+
+        let n: RawNumber = "1e400".parse().unwrap();
+        assert_eq!(n.as_str(), "1e400");
+        assert_eq!(
+            f64::try_from(&n).unwrap_err().syntax(),
+            Some(SyntaxError::NumberOutOfRange)
+        );
+
+        for invalid in ["", "01", "1.", "+1", " 1", "1 ", "1x"] {
+            let err = invalid.parse::<RawNumber>().unwrap_err();
+            assert_eq!(
+                err.syntax(),
+                Some(SyntaxError::InvalidNumber),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn subparsers_see_options() {
+        let mut p = Parser::from_str(r#"{"a": [1.10]}"#)
+            .with_options(ParserOptions::new().number_mode(NumberMode::Raw));
+
+        let mut obj = p.next().as_object().unwrap();
+        let mut arr = obj.next().unwrap().unwrap().value().as_array().unwrap();
+        assert_eq!(arr.next().as_raw_number().unwrap().as_str(), "1.10");
+    }
+}
+
+mod wide_integers {
+    use super::*;
+    use json_stream::emit::{Emit, Emitter};
+
+    #[test]
+    fn parsed_losslessly() {
+        let mut p = Parser::new(
+            "18446744073709551616 -9223372036854775809 340282366920938463463374607431768211455 \
+             -170141183460469231731687303715884105728 340282366920938463463374607431768211456"
+                .as_bytes(),
+        );
+
+        let n = p.next().as_number().unwrap();
+        assert_eq!(u128::try_from(n).ok(), Some(u64::MAX as u128 + 1));
+        assert!(n.is_integer() && !n.is_u64());
+        assert_eq!(
+            u64::try_from(n).unwrap_err().syntax(),
+            Some(SyntaxError::NumberOutOfRange)
+        );
+
+        let n = p.next().as_number().unwrap();
+        assert_eq!(i128::try_from(n).ok(), Some(i64::MIN as i128 - 1));
+        assert_eq!(
+            u128::try_from(n).unwrap_err().syntax(),
+            Some(SyntaxError::NumberOutOfRange)
+        );
+
+        assert_eq!(
+            p.next().as_number().map(u128::try_from).unwrap().ok(),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            p.next().as_number().map(i128::try_from).unwrap().ok(),
+            Some(i128::MIN)
+        );
+
+        // beyond 128 bits, integers still become floats
+        let n = p.next().as_number().unwrap();
+        assert!(n.is_f64());
+        assert_eq!(n.as_f64(), 2f64.powi(128));
+    }
+
+    #[test]
+    fn canonical_repr() {
+        assert_eq!(Number::from(5u128).as_u64(), Some(5));
+        assert_eq!(Number::from(-5i128).as_i64(), Some(-5));
+        assert_eq!(Number::from(5i128), Number::from(5u8));
+        assert!(Number::from(u128::MAX) > Number::from(u64::MAX));
+        assert!(Number::from(i128::MIN) < Number::from(i64::MIN));
+        assert!(Number::from(u128::MAX) < Number::from(1e39));
+        assert!(Number::from(i128::MIN) > Number::from(-1e39));
+        assert_eq!(Number::from(1u128 << 100), Number::from(2f64.powi(100)));
+        assert_eq!(Number::from(u128::MAX).to_string(), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn emitter_round_trip() {
+        let values = [u128::MAX, u64::MAX as u128 + 1];
+        let signed = [i128::MIN, i128::MAX];
+        let mut buf = vec![];
+        {
+            let mut e = Emitter::new(&mut buf);
+            e.emit(&values).unwrap();
+            e.emit(&signed).unwrap();
+        }
+
+        let mut p = Parser::from_slice(&buf);
+        {
+            let mut arr = p.next().as_array().unwrap();
+            for v in values {
+                assert_eq!(arr.next().as_number(), Some(Number::from(v)));
+            }
+        }
+        let mut arr = p.next().as_array().unwrap();
+        for v in signed {
+            let n = arr.next().as_number().unwrap();
+            assert_eq!(i128::try_from(n).ok(), Some(v));
+        }
+    }
+}
+
+mod depth_limit {
+    use super::*;
+
+    #[test]
+    fn too_deep_is_skipped() {
+        let mut p =
+            Parser::from_str("[[[1, [2]]], 3] 4").with_options(ParserOptions::new().max_depth(2));
+
+        {
+            let mut outer = p.next().as_array().unwrap();
+            {
+                let mut inner = outer.next().as_array().unwrap();
+                let err = inner.next().unwrap().unwrap_err();
+                assert_eq!(err.syntax(), Some(SyntaxError::RecursionLimitExceeded));
+                assert_eq!(err.position().unwrap().offset(), 3);
+                assert!(inner.next().is_none());
+            }
+            assert_eq!(outer.next().as_number(), Some(Number::from(3)));
+            assert!(outer.next().is_none());
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(4)));
+    }
+
+    #[test]
+    fn closed_and_skipped_values_release_depth() {
+        let mut p = Parser::from_str(r#"[[1], {"a": [2]}, [[3]], [4]]"#)
+            .with_options(ParserOptions::new().max_depth(2));
+
+        let mut arr = p.next().as_array().unwrap();
+        {
+            let mut inner = arr.next().as_array().unwrap();
+            assert_eq!(inner.next().as_number(), Some(Number::from(1)));
+            assert!(inner.next().is_none());
+        }
+        {
+            // dropped before its value was read
+            let mut obj = arr.next().as_object().unwrap();
+            obj.next().unwrap().unwrap();
+        }
+        // skipped without reading, even though it is too deep
+        arr.next().as_array().unwrap();
+        let mut inner = arr.next().as_array().unwrap();
+        assert_eq!(inner.next().as_number(), Some(Number::from(4)));
+    }
+
+    #[test]
+    fn default_limit() {
+        let ok = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        let mut p = Parser::from_str(&ok);
+        assert!(p.next().unwrap().is_ok());
+        assert!(p.next().is_none());
+
+        let mut p = Parser::from_str("[[]] [[]]").with_options(ParserOptions::new().max_depth(1));
+        {
+            let mut arr = p.next().as_array().unwrap();
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::RecursionLimitExceeded));
+        }
+        assert!(p.next().is_array());
+    }
+
+    #[test]
+    fn skipping_does_not_recurse() {
+        let deep = 1_000_000;
+        let json = format!(
+            r#"[{}{}] {{"a": {}{}, "b": 2}} 3"#,
+            "[{\"x\":".repeat(deep),
+            "0}]".repeat(deep),
+            "[".repeat(deep),
+            "]".repeat(deep)
+        );
+        let mut p = Parser::from_str(&json);
+
+        assert!(p.next().is_array());
+        {
+            let mut obj = p.next().as_object().unwrap();
+            obj.next().unwrap().unwrap();
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned(), Ok("b".to_owned()));
+            assert_eq!(kv.value().as_number(), Some(Number::from(2)));
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(3)));
+    }
+}
+
+mod options {
+    use super::*;
+
+    fn errors_and_numbers(p: &mut Parser<&[u8]>) -> Vec<std::result::Result<Number, SyntaxError>> {
+        let mut arr = p.next().as_array().unwrap();
+        let mut seen = vec![];
+        while let Some(item) = arr.next() {
+            seen.push(
+                item.map(|j| j.as_number().unwrap())
+                    .map_err(|e| e.syntax().unwrap()),
+            );
+        }
+        seen
+    }
+
+    #[test]
+    fn strict_by_default() {
+        let mut p = Parser::from_str("[1, 2,] [1 2]");
+        assert_eq!(
+            errors_and_numbers(&mut p),
+            [
+                Ok(Number::from(1)),
+                Ok(Number::from(2)),
+                Err(SyntaxError::TrailingComma)
+            ]
+        );
+        assert_eq!(
+            errors_and_numbers(&mut p),
+            [
+                Ok(Number::from(1)),
+                Err(SyntaxError::MissingComma),
+                Ok(Number::from(2))
+            ]
+        );
+
+        let mut p = Parser::from_str(r#"{"a": 1 "b": 2,}"#);
+        let mut obj = p.next().as_object().unwrap();
+        obj.next().unwrap().unwrap();
+        let err = obj.next().unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(SyntaxError::MissingComma));
+        assert_eq!(err.position().unwrap().offset(), 8);
+        obj.next().unwrap().unwrap();
+        let err = obj.next().unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(SyntaxError::TrailingComma));
+        assert!(obj.next().is_none());
+    }
+
+    #[test]
+    fn control_characters_in_strings() {
+        let text = "[\"a\tb\", \"c\nd\", \"\u{1}\", \"e\\tf\", \"\u{7f}\"]";
+        let read_all = |options: ParserOptions| {
+            let mut p = Parser::from_str(text).with_options(options);
+            let mut arr = p.next().as_array().unwrap();
+            let mut seen = vec![];
+            while let Some(item) = arr.next() {
+                let s = item.as_string().unwrap();
+                seen.push(s.read_owned().map_err(|e| e.syntax().unwrap()));
+            }
+            seen
+        };
+        let control = SyntaxError::ControlCharacterWhileParsingString;
+        assert_eq!(
+            read_all(ParserOptions::strict()),
+            [
+                Err(control),
+                Err(control),
+                Err(control),
+                Ok("e\tf".to_owned()),
+                Ok("\u{7f}".to_owned())
+            ]
+        );
+        assert_eq!(
+            read_all(ParserOptions::new().allow_control_characters(true)),
+            ["a\tb", "c\nd", "\u{1}", "e\tf", "\u{7f}"].map(|s| Ok(s.to_owned()))
+        );
+
+        // also when read as borrowed text or one char at a time
+        let mut p = Parser::from_str("\"a\tb\" \"\tc\"");
+        let err = p.next().as_string().unwrap().read_cow().unwrap_err();
+        assert_eq!(err.syntax(), Some(control));
+        assert_eq!(err.position().unwrap().offset(), 2);
+        let mut chars = p.next().as_string().unwrap().read_chars();
+        let err = chars.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(control));
+    }
+
+    #[test]
+    fn object_leading_and_double_commas() {
+        let mut p =
+            Parser::from_str(r#"{, "a": 1,, "b": 2}"#).with_options(ParserOptions::lenient());
+        let mut obj = p.next().as_object().unwrap();
+        assert_eq!(
+            obj.next().unwrap().err().unwrap().syntax(),
+            Some(SyntaxError::TrailingComma)
+        );
+        obj.next().unwrap().unwrap();
+        assert_eq!(
+            obj.next().unwrap().err().unwrap().syntax(),
+            Some(SyntaxError::TrailingComma)
+        );
+        let mut kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_owned().unwrap(), "b");
+        drop(kv);
+        assert!(obj.next().is_none());
+    }
+
+    #[test]
+    fn lenient_commas() {
+        let mut p = Parser::from_str("[1, 2,] [1 2 [3,],]").with_options(ParserOptions::lenient());
+        assert_eq!(
+            errors_and_numbers(&mut p),
+            [Ok(Number::from(1)), Ok(Number::from(2))]
+        );
+
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+        {
+            // subparsers see the same options
+            let mut inner = arr.next().as_array().unwrap();
+            assert_eq!(inner.next().as_number(), Some(Number::from(3)));
+            assert!(inner.next().is_none());
+        }
+        assert!(arr.next().is_none());
+
+        let options = ParserOptions::strict().allow_trailing_commas(true);
+        let mut p = Parser::from_str("[1 2,]").with_options(options);
+        assert_eq!(
+            errors_and_numbers(&mut p),
+            [
+                Ok(Number::from(1)),
+                Err(SyntaxError::MissingComma),
+                Ok(Number::from(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_keys() {
+        let mut p = Parser::from_str(r#"{a: 1, $b_2 : [x], "c": 3, d: 4, e:5} 6"#)
+            .with_options(ParserOptions::lenient());
+
+        {
+            let mut obj = p.next().as_object().unwrap();
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "a");
+            assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+
+            // skipped along with a value that isn't valid json
+            let mut kv = obj.next().unwrap().unwrap();
+            let chars: String = kv.key().unwrap().read_chars().map(|c| c.unwrap()).collect();
+            assert_eq!(chars, "$b_2");
+            drop(kv);
+
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_cow().unwrap(), "c");
+            drop(kv);
+
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_cow().unwrap(), "d");
+            drop(kv);
+
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(5)));
+            assert!(obj.next().is_none());
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(6)));
+    }
+}
+
+mod relaxed {
+    use super::*;
+
+    #[test]
+    fn comments() {
+        let mut p = Parser::from_str(
+            r#"// leading
+            [1, /* two */ 2 // end of line
+            , 3,] /**/ {"a" /* before colon */ : /* after */ 4, /* last */}
+            // trailing"#,
+        )
+        .with_options(ParserOptions::jsonc());
+        {
+            let mut arr = p.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(3)));
+            assert!(arr.next().is_none());
+        }
+        {
+            let mut obj = p.next().as_object().unwrap();
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "a");
+            assert_eq!(kv.value().as_number(), Some(Number::from(4)));
+            assert!(obj.next().is_none());
+        }
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn skipped_values_with_comments() {
+        let mut p = Parser::from_str(
+            r#"[[1, /* ] */ '] }', // ]
+            {a: "}"}], 2] {a: [/* } */], b: 'x'}"#,
+        )
+        .with_options(ParserOptions::json5());
+        {
+            let mut arr = p.next().as_array().unwrap();
+            drop(arr.next());
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            assert!(arr.next().is_none());
+        }
+        {
+            let mut obj = p.next().as_object().unwrap();
+            drop(obj.next());
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "b");
+            assert_eq!(kv.value().as_string().unwrap().read_owned().unwrap(), "x");
+        }
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn invalid_comment() {
+        let mut p = Parser::from_str("[1, / 2, 3]").with_options(ParserOptions::jsonc());
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidComment));
+        assert!(!err.is_fatal());
+        assert_eq!(arr.next().as_number(), Some(Number::from(3)));
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn single_quotes() {
+        let mut p = Parser::from_str(r#"{'it\'s': 'say "hi"', key: 'caf\u00e9'}"#)
+            .with_options(ParserOptions::json5());
+        let mut obj = p.next().as_object().unwrap();
+
+        let mut kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_owned().unwrap(), "it's");
+        let value = kv.value().as_string().unwrap();
+        assert_eq!(value.read_owned().unwrap(), r#"say "hi""#);
+
+        let mut kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_owned().unwrap(), "key");
+        let value = kv.value().as_string().unwrap();
+        assert_eq!(value.read_owned().unwrap(), "café");
+        assert!(obj.next().is_none());
+    }
+
+    #[test]
+    fn hex_numbers() {
+        let mut p = Parser::from_str(
+            "[0x1F, -0xff, 0XaBc, 0x, 0x1g, 0x100000000000000000000000000000000, 7]",
+        )
+        .with_options(ParserOptions::json5());
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(31)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(-255)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(0xabc)));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::NumberOutOfRange));
+        assert_eq!(arr.next().as_number(), Some(Number::from(7)));
+        assert!(arr.next().is_none());
+
+        let options = ParserOptions::json5().number_mode(NumberMode::Raw);
+        let mut p = Parser::from_str("0x10").with_options(options);
+        let raw = p.next().unwrap().unwrap();
+        assert!(matches!(raw, Json::RawNumber(n) if n.as_str() == "16"));
+    }
+
+    #[test]
+    fn infinity_and_nan() {
+        let mut p = Parser::from_str("[Infinity, -Infinity, NaN, Infinit]")
+            .with_options(ParserOptions::json5());
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(f64::INFINITY)));
+        assert_eq!(
+            arr.next().as_number(),
+            Some(Number::from(f64::NEG_INFINITY))
+        );
+        assert!(arr.next().as_number().unwrap().as_f64().is_nan());
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn relaxed_numbers() {
+        let mut p =
+            Parser::from_str("[+1, .5, -.25, 5., 5.e1, +0x10, +Infinity, -NaN, +, ., 1.., 7]")
+                .with_options(ParserOptions::json5());
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(0.5)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(-0.25)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(5.0)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(50.0)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(16)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(f64::INFINITY)));
+        assert!(arr.next().as_number().unwrap().as_f64().is_nan());
+        for _ in 0..3 {
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        }
+        assert_eq!(arr.next().as_number(), Some(Number::from(7)));
+        assert!(arr.next().is_none());
+
+        // raw numbers are rewritten as valid JSON
+        let options = ParserOptions::json5().number_mode(NumberMode::Raw);
+        let mut p = Parser::from_str("[+1, .5, -.5e1, 5., 5.E1]").with_options(options);
+        let mut arr = p.next().as_array().unwrap();
+        for expected in ["1", "0.5", "-0.5e1", "5.0", "5.0E1"] {
+            let raw = match arr.next() {
+                Some(Ok(Json::RawNumber(n))) => n,
+                _ => panic!("expected a raw number"),
+            };
+            assert_eq!(raw.as_str(), expected);
+            assert!(expected.parse::<RawNumber>().is_ok());
+        }
+    }
+
+    #[test]
+    fn js_escapes() {
+        use std::io::Read;
+
+        let src = "['\\x41\\v\\0\\a\\é', 'one \\\n two \\\r\n three \\\r four \\\u{2028}', \
+            '\\01', '\\1', '\\x4', 'ok']";
+        let expected = ["A\u{b}\0aé", "one  two  three  four "];
+
+        let mut p = Parser::from_str(src).with_options(ParserOptions::json5());
+        let mut arr = p.next().as_array().unwrap();
+        for s in expected {
+            assert_eq!(arr.next().as_string().unwrap().read_owned().unwrap(), s);
+        }
+        for _ in 0..3 {
+            let s = arr.next().as_string().unwrap();
+            let err = s.read_owned().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidEscape));
+        }
+        assert_eq!(arr.next().as_string().unwrap().read_owned().unwrap(), "ok");
+        assert!(arr.next().is_none());
+
+        // the other string readers decode the same
+        let mut p = Parser::from_str(src).with_options(ParserOptions::json5());
+        let mut arr = p.next().as_array().unwrap();
+        let chars = arr.next().as_string().unwrap().read_chars();
+        assert_eq!(chars.collect::<Result<String, _>>().unwrap(), expected[0]);
+        let mut bytes = vec![];
+        let mut s = arr.next().as_string().unwrap().read_bytes();
+        s.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, expected[1].as_bytes());
+    }
+
+    #[test]
+    fn strict_rejects_relaxed_syntax() {
+        for s in ["/* c */ 1", "'a'", "Infinity", "-Infinity", "NaN"] {
+            let mut p = Parser::from_str(s);
+            assert!(p.next().unwrap().is_err(), "{s}");
+        }
+
+        let mut p = Parser::from_str("[0x1F]");
+        let mut arr = p.next().as_array().unwrap();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+        assert!(arr.next().is_none());
+
+        let mut p = Parser::from_str("{a: 1}").with_options(ParserOptions::jsonc());
+        let mut obj = p.next().as_object().unwrap();
+        let err = obj.next().unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(SyntaxError::KeyMustBeAString));
+
+        for s in [r#""\x41""#, r#""\v""#, "\"\\\nx\""] {
+            let mut p = Parser::from_str(s).with_options(ParserOptions::jsonc());
+            let err = p.next().as_string().unwrap().read_owned().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidEscape), "{s}");
+        }
+    }
+}
+
+mod malformed {
+    use super::*;
+
+    fn err_of<T>(item: Option<Result<T, Error>>) -> Option<SyntaxError> {
+        item.unwrap().err().unwrap().syntax()
+    }
+
+    #[test]
+    fn unexpected_value_bytes() {
+        let mut p = Parser::from_str("} ] @ [:, 1] 2");
+
+        for _ in 0..3 {
+            assert_eq!(err_of(p.next()), Some(SyntaxError::ExpectedSomeValue));
+        }
+        {
+            let mut arr = p.next().as_array().unwrap();
+            assert_eq!(err_of(arr.next()), Some(SyntaxError::ExpectedSomeValue));
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert!(arr.next().is_none());
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(2)));
+    }
+
+    #[test]
+    fn non_string_keys() {
+        let mut p = Parser::from_str(r#"{1: [2], "a": 3, x y: {"z": ","}, 'b': 4, "c": 5} 6"#);
+
+        {
+            let mut obj = p.next().as_object().unwrap();
+            let err = obj.next().unwrap().err().unwrap();
+            assert_eq!(err.syntax(), Some(SyntaxError::KeyMustBeAString));
+            assert_eq!(err.position().unwrap().offset(), 1);
+
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "a");
+            assert_eq!(kv.value().as_number(), Some(Number::from(3)));
+
+            assert_eq!(err_of(obj.next()), Some(SyntaxError::KeyMustBeAString));
+            assert_eq!(err_of(obj.next()), Some(SyntaxError::KeyMustBeAString));
+
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(5)));
+            assert!(obj.next().is_none());
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(6)));
+    }
+
+    #[test]
+    fn missing_colon_and_value() {
+        let mut p = Parser::from_str(r#"{"a" [1, 2], "b": , "c" } {"d""#);
+
+        {
+            let mut obj = p.next().as_object().unwrap();
+            let err = obj.next().unwrap().unwrap().value().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::ExpectedColon));
+            assert_eq!(err.position().unwrap().offset(), 5);
+
+            let err = obj.next().unwrap().unwrap().value().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::ExpectedSomeValue));
+
+            let err = obj.next().unwrap().unwrap().value().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::ExpectedColon));
+            assert!(obj.next().is_none());
+        }
+
+        let mut obj = p.next().as_object().unwrap();
+        let err = obj.next().unwrap().unwrap().value().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+    }
+
+    #[test]
+    fn dropped_entries_are_checked() {
+        let mut p = Parser::from_str(r#"{"a" 1, "b": 2} {"a": @, "b": 3} {"a": , "b": 4}"#);
+
+        for (code, n) in [
+            (SyntaxError::ExpectedColon, 2),
+            (SyntaxError::ExpectedSomeValue, 3),
+            (SyntaxError::ExpectedSomeValue, 4),
+        ] {
+            let mut obj = p.next().as_object().unwrap();
+            drop(obj.next().unwrap().unwrap());
+            assert_eq!(err_of(obj.next()), Some(code));
+
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "b");
+            assert_eq!(kv.value().as_number(), Some(Number::from(n)));
+            assert!(obj.next().is_none());
+        }
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn key_read_twice() {
+        let mut p = Parser::from_str(r#"{"a": 1}"#);
+        let mut obj = p.next().as_object().unwrap();
+        let mut kv = obj.next().unwrap().unwrap();
+
+        assert_eq!(kv.key().unwrap().read_owned().unwrap(), "a");
+        let err = kv.key().err().unwrap();
+        assert_eq!(err.syntax(), None);
+        assert!(err.position().is_none());
+        assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+    }
+}
+
+mod recovery {
+    use super::*;
+
+    #[test]
+    fn resync_in_array() {
+        let mut p = Parser::from_str(r#"[1, tru e, 2.3.4 [5], {"a": 6} x, "s", -] 7"#);
+
+        {
+            let mut arr = p.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+            assert!(!err.is_fatal());
+
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+            assert!(!err.is_fatal());
+
+            // the object is fine, only what follows it is skipped
+            let mut obj = arr.next().as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(6)));
+            assert!(obj.next().is_none());
+            drop(obj);
+
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::MissingComma));
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+
+            assert_eq!(arr.next().as_string().unwrap().read_owned().unwrap(), "s");
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+            assert!(arr.next().is_none());
+        }
+        assert_eq!(p.next().as_number(), Some(Number::from(7)));
+    }
+
+    #[test]
+    fn resync_in_object() {
+        let mut p = Parser::from_str(
+            r#"[{"id": 1, "v": nul}, {"id": tru e, "n": 2}, {"id" 3, "n": 4}, {"id": 4.0.1, "n": 5}, {"n": [6]}]"#,
+        );
+
+        let mut records = p.next().as_array().unwrap();
+        let mut seen = vec![];
+        while let Some(record) = records.next() {
+            let mut obj = record.as_object().unwrap();
+            while let Some(kv) = obj.next() {
+                let mut kv = kv.unwrap();
+                let key = kv.key().unwrap().read_owned().unwrap();
+                let value = kv.value().map(|v| v.as_number()).map_err(|e| e.syntax());
+                seen.push((key, value));
+            }
+        }
+
+        let expected = [
+            ("id", Ok(Some(1))),
+            ("v", Err(SyntaxError::InvalidIdentifier)),
+            ("id", Err(SyntaxError::InvalidIdentifier)),
+            ("n", Ok(Some(2))),
+            ("id", Err(SyntaxError::ExpectedColon)),
+            ("n", Ok(Some(4))),
+            ("id", Err(SyntaxError::InvalidNumber)),
+            ("n", Ok(Some(5))),
+            ("n", Ok(None)),
+        ]
+        .map(|(k, v)| (k.to_owned(), v.map(|n| n.map(Number::from)).map_err(Some)));
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn fatal_errors() {
+        let mut p = Parser::from_str(r#"["abc"#);
+        let mut arr = p.next().as_array().unwrap();
+        let err = arr.next().as_string().unwrap().read_owned().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+        assert!(err.is_fatal());
+        // the array was cut off too
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(arr.next().is_none());
+
+        let mut p = Parser::from_str("[1, fals");
+        let mut arr = p.next().as_array().unwrap();
+        arr.next();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingValue));
+        assert!(err.is_fatal());
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(arr.next().is_none());
+
+        let mut p = Parser::from_str("[1, 2");
+        let mut arr = p.next().as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(err.is_fatal());
+        assert!(arr.next().is_none());
+        drop(arr);
+        assert!(p.next().is_none());
+
+        let mut p = Parser::from_str(r#"{"a": 1"#);
+        let mut obj = p.next().as_object().unwrap();
+        let kv = obj.next().unwrap().unwrap();
+        assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+        let err = obj.next().unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+        assert!(err.is_fatal());
+        assert!(obj.next().is_none());
+
+        // cut off between items
+        let mut p = Parser::from_str(r#"[{"a": 1},"#);
+        let mut arr = p.next().as_array().unwrap();
+        assert!(arr.next().as_object().is_some());
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+    }
+
+    #[test]
+    fn truncated_while_skipping() {
+        let mut p = Parser::from_str("[1, [2, 3");
+        assert!(p.next().is_array());
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(err.is_fatal());
+        assert!(p.next().is_none());
+
+        // the outermost skipped value is reported
+        let mut p = Parser::from_str(r#"{"a": [1"#);
+        assert!(p.next().is_object());
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+
+        let mut p = Parser::from_str(r#"[{"a": "b]}"#);
+        assert!(p.next().is_array());
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+
+        let mut p = Parser::from_str(r#""abc"#);
+        assert!(p.next().is_string());
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+
+        let mut p = Parser::from_str("[[1, 2");
+        let mut arr = p.next().as_array().unwrap();
+        assert!(arr.next().is_array());
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        // the outer array was cut off too
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(arr.next().is_none());
+
+        // the rest of an invalid item
+        let mut p = Parser::from_str("[tru [1, 2");
+        let mut arr = p.next().as_array().unwrap();
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+        let err = arr.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+        assert!(err.is_fatal());
+    }
+}
+
+mod single_document {
+    use super::*;
+
+    #[test]
+    fn root_then_end() {
+        let mut p = Parser::from_str(" [1, 2] \n").single();
+        {
+            let mut arr = p.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        }
+        assert!(p.next().is_none());
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn trailing_characters() {
+        let mut p = Parser::from_str(r#"{"a": 1} x {"b": 2}"#).single();
+        assert!(p.next().is_object());
+
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::TrailingCharacters));
+        assert_eq!(err.position().unwrap().offset(), 9);
+        assert!(err.is_fatal());
+        assert!(p.next().is_none());
+
+        let mut p = Parser::from_str("12]").single();
+        assert_eq!(p.next().as_number(), Some(Number::from(12)));
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::TrailingCharacters));
+    }
+
+    #[test]
+    fn truncated_root() {
+        let mut p = Parser::from_str("[1, 2").single();
+        {
+            let mut arr = p.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+            assert!(err.is_fatal());
+        }
+        assert!(p.next().is_none());
+
+        let mut p = Parser::from_str(r#"{"a": 1"#).single();
+        {
+            let mut obj = p.next().as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+            let err = obj.next().unwrap().err().unwrap();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+            assert!(err.is_fatal());
+        }
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn missing_root() {
+        let mut p = Parser::from_str("  ").single();
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingValue));
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn invalid_root() {
+        let mut p = Parser::from_str("nope ").single();
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+        assert!(p.next().is_none());
+    }
+}
+
+mod json_lines {
+    use super::*;
+
+    fn line_number(r: &mut JsonLines<impl std::io::BufRead>) -> (u64, Option<Number>) {
+        let (line, item) = r.next().unwrap();
+        (line, item.as_number())
+    }
+
+    fn line_error(r: &mut JsonLines<impl std::io::BufRead>) -> (u64, Option<SyntaxError>) {
+        let (line, item) = r.next().unwrap();
+        (line, item.err().and_then(|e| e.syntax()))
+    }
+
+    #[test]
+    fn values_with_line_numbers() {
+        let mut r = JsonLines::from_buf_read("{\"a\": 1}\n\n  [2, 3]\r\n\"four\"\n5".as_bytes());
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 1);
+            let mut obj = item.as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+        }
+        {
+            // skipped without reading it
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 3);
+            assert!(item.is_array());
+        }
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 4);
+            assert_eq!(item.as_string().unwrap().read_owned().unwrap(), "four");
+        }
+        assert_eq!(line_number(&mut r), (5, Some(Number::from(5))));
+        assert!(r.next().is_none());
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn errors_stay_on_their_line() {
+        let mut r = JsonLines::from_buf_read(
+            "[1, 2\n{\"a\": tru}\n\"unterminated\n@ 6 7\n[8]\n".as_bytes(),
+        );
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 1);
+            let mut arr = item.as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            // the newline ends the array like the end of the input would
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+            assert!(arr.next().is_none());
+        }
+        {
+            // dropped with an error inside
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 2);
+            assert!(item.is_object());
+        }
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 3);
+            let err = item.as_string().unwrap().read_owned().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+            assert!(err.is_fatal());
+            assert_eq!(err.position().unwrap().line(), 3);
+        }
+
+        // the rest of an invalid line is not reported
+        assert_eq!(
+            line_error(&mut r),
+            (4, Some(SyntaxError::ExpectedSomeValue))
+        );
+
+        let (line, item) = r.next().unwrap();
+        assert_eq!(line, 5);
+        let mut arr = item.as_array().unwrap();
+        assert_eq!(arr.next().as_number(), Some(Number::from(8)));
+        drop(arr);
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn truncated_containers() {
+        let mut r = JsonLines::from_buf_read("[1, 2\n[3]\n{\"a\": 1\n{\"b\": 4}\n".as_bytes());
+        {
+            let (line, item) = r.next().unwrap();
+            let mut arr = item.as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+            assert_eq!((line, err.position().unwrap().line()), (1, 1));
+        }
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 2);
+            let mut arr = item.as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(3)));
+            assert!(arr.next().is_none());
+        }
+        {
+            let (line, item) = r.next().unwrap();
+            let mut obj = item.as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+            let err = obj.next().unwrap().err().unwrap();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+            assert_eq!((line, err.position().unwrap().line()), (3, 3));
+        }
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 4);
+            let mut obj = item.as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(4)));
+            assert!(obj.next().is_none());
+        }
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn dropped_truncated_lines() {
+        let mut r = JsonLines::from_buf_read("[1,\n{\"a\": [2\n\"bc\n[3]\n".as_bytes());
+        assert!(r.next().unwrap().1.is_array());
+        assert_eq!(
+            line_error(&mut r),
+            (1, Some(SyntaxError::EofWhileParsingList))
+        );
+        assert!(r.next().unwrap().1.is_object());
+        assert_eq!(
+            line_error(&mut r),
+            (2, Some(SyntaxError::EofWhileParsingObject))
+        );
+        assert!(r.next().unwrap().1.is_string());
+        assert_eq!(
+            line_error(&mut r),
+            (3, Some(SyntaxError::EofWhileParsingString))
+        );
+
+        {
+            let (line, item) = r.next().unwrap();
+            assert_eq!(line, 4);
+            assert!(item.is_array());
+        }
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn trailing_characters() {
+        let mut r = JsonLines::from_buf_read("1 2\n[3] x\n4  \n".as_bytes());
+
+        assert_eq!(line_number(&mut r), (1, Some(Number::from(1))));
+        assert_eq!(
+            line_error(&mut r),
+            (1, Some(SyntaxError::TrailingCharacters))
+        );
+
+        assert_eq!(r.next().unwrap().0, 2);
+        assert_eq!(
+            line_error(&mut r),
+            (2, Some(SyntaxError::TrailingCharacters))
+        );
+
+        assert_eq!(line_number(&mut r), (3, Some(Number::from(4))));
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn split_across_refills() {
+        let src = "[\"abc\", 12345]\n\n{\"key\": \"value\"} []\n67890\n";
+        let mut r = JsonLines::from_buf_read(std::io::BufReader::with_capacity(3, src.as_bytes()));
+        {
+            let mut arr = r.next().unwrap().1.as_array().unwrap();
+            assert_eq!(arr.next().as_string().unwrap().read_owned().unwrap(), "abc");
+        }
+        assert_eq!(r.next().unwrap().0, 3);
+        assert_eq!(
+            line_error(&mut r),
+            (3, Some(SyntaxError::TrailingCharacters))
+        );
+        assert_eq!(line_number(&mut r), (4, Some(Number::from(67890))));
+        assert!(r.next().is_none());
+        assert_eq!(r.position().offset(), src.len() as u64);
+    }
+
+    #[test]
+    fn values_cannot_span_lines() {
+        let mut r = JsonLines::from_buf_read("{\"a\":\n1}\n".as_bytes())
+            .with_options(ParserOptions::lenient());
+        {
+            let mut obj = r.next().unwrap().1.as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            let err = kv.value().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingValue));
+        }
+        // the dropped object was cut off too
+        assert_eq!(
+            line_error(&mut r),
+            (1, Some(SyntaxError::EofWhileParsingObject))
+        );
+
+        assert_eq!(line_number(&mut r), (2, Some(Number::from(1))));
+        assert_eq!(
+            line_error(&mut r),
+            (2, Some(SyntaxError::TrailingCharacters))
+        );
+        assert!(r.next().is_none());
+    }
+}
+
+mod json_seq {
+    use super::*;
+
+    fn seq_error(r: &mut JsonSeq<&[u8]>) -> Option<SyntaxError> {
+        r.next().unwrap().err().and_then(|e| e.syntax())
+    }
+
+    #[test]
+    fn records() {
+        let mut r = JsonSeq::from_buf_read(
+            "\x1e{\"a\": [1,\n2]}\n\x1e\x1e \n\x1e\"b\"\n\x1e3\n\x1etrue\r\n".as_bytes(),
+        );
+        {
+            let mut obj = r.next().as_object().unwrap();
+            let mut kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().unwrap(), "a");
+            // values may span lines, and are skipped up to the next record
+        }
+        assert_eq!(r.next().as_string().unwrap().read_owned().unwrap(), "b");
+        assert_eq!(r.next().as_number(), Some(Number::from(3)));
+        assert_eq!(r.next().as_bool(), Some(true));
+        assert!(r.next().is_none());
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn truncated_records() {
+        let mut r = JsonSeq::from_buf_read(
+            "\x1e{\"a\": [1, \"b\x1e123\x1e[4]\n\x1enul\x1enull\x1e\"c\"".as_bytes(),
+        );
+        {
+            let mut obj = r.next().as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            let mut arr = kv.value().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            let err = arr.next().as_string().unwrap().read_owned().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+        }
+        // the dropped array was cut off too
+        assert_eq!(seq_error(&mut r), Some(SyntaxError::EofWhileParsingList));
+
+        // a number that isn't followed by whitespace may have been cut short
+        let err = r.next().unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingValue));
+        assert_eq!(err.position().unwrap().offset(), 17);
+
+        {
+            let mut arr = r.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(4)));
+        }
+        assert_eq!(seq_error(&mut r), Some(SyntaxError::EofWhileParsingValue));
+        assert_eq!(seq_error(&mut r), Some(SyntaxError::EofWhileParsingValue));
+
+        // only scalars need to be followed by whitespace
+        assert_eq!(r.next().as_string().unwrap().read_owned().unwrap(), "c");
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn truncated_containers() {
+        let mut r =
+            JsonSeq::from_buf_read("\x1e[1, 2\x1e3\n\x1e{\"a\": 1\x1e{\"b\": 4}\n".as_bytes());
+        {
+            let mut arr = r.next().as_array().unwrap();
+            assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+            assert_eq!(arr.next().as_number(), Some(Number::from(2)));
+            let err = arr.next().unwrap().unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+            assert!(arr.next().is_none());
+        }
+        assert_eq!(r.next().as_number(), Some(Number::from(3)));
+        {
+            let mut obj = r.next().as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+            let err = obj.next().unwrap().err().unwrap();
+            assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+        }
+        {
+            let mut obj = r.next().as_object().unwrap();
+            let kv = obj.next().unwrap().unwrap();
+            assert_eq!(kv.value().as_number(), Some(Number::from(4)));
+            assert!(obj.next().is_none());
+        }
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn trailing_characters_and_missing_separator() {
+        let mut r = JsonSeq::from_buf_read("[1]\n\x1e2 3\n\x1e4\n".as_bytes());
+        assert!(r.next().is_array());
+        assert_eq!(r.next().as_number(), Some(Number::from(2)));
+        assert_eq!(seq_error(&mut r), Some(SyntaxError::TrailingCharacters));
+        assert_eq!(r.next().as_number(), Some(Number::from(4)));
+        assert!(r.next().is_none());
+    }
+
+    #[test]
+    fn emitter_round_trip() {
+        use json_stream::emit::{Emit, Emitter, EmitterOptions, Separator};
+
+        let mut buf = vec![];
+        {
+            let options = EmitterOptions::new().separator(Separator::RecordSeparator);
+            let mut e = Emitter::new(&mut buf).with_options(options);
+            e.emit(&1).unwrap();
+            e.array().unwrap().emit("two").unwrap();
+            e.emit(&3).unwrap();
+            e.flush().unwrap();
+        }
+        assert_eq!(buf, b"\x1e1\n\x1e[\"two\"]\n\x1e3\n");
+
+        let mut r = JsonSeq::from_buf_read(buf.as_slice());
+        assert_eq!(r.next().as_number(), Some(Number::from(1)));
+        {
+            let mut arr = r.next().as_array().unwrap();
+            assert_eq!(arr.next().as_string().unwrap().read_owned().unwrap(), "two");
+        }
+        assert_eq!(r.next().as_number(), Some(Number::from(3)));
+        assert!(r.next().is_none());
+    }
+}
+
+mod stream_modes {
+    use super::*;
+
+    fn roots(s: &str, mode: StreamMode) -> Vec<std::result::Result<String, SyntaxError>> {
+        let mut p = Parser::from_str(s).stream_mode(mode);
+        let mut seen = vec![];
+        while let Some(item) = p.next() {
+            seen.push(match item {
+                Ok(Json::Null) => Ok("null".to_owned()),
+                Ok(Json::Bool(b)) => Ok(b.to_string()),
+                Ok(Json::Number(n)) => Ok(n.to_string()),
+                Ok(Json::String(s)) => Ok(format!("{:?}", s.read_owned().unwrap())),
+                Ok(Json::Array(_)) => Ok("[]".to_owned()),
+                Ok(Json::Object(_)) => Ok("{}".to_owned()),
+                Ok(Json::RawNumber(_)) => unreachable!(),
+                Err(e) => Err(e.syntax().unwrap()),
+            });
+        }
+        seen
+    }
+
+    fn ok(values: &[&str]) -> Vec<std::result::Result<String, SyntaxError>> {
+        values.iter().map(|v| Ok(v.to_string())).collect()
+    }
+
+    #[test]
+    fn concatenated() {
+        let mode = StreamMode::Concatenated;
+        assert_eq!(
+            roots(r#"{"a":1}{"b":2}[3]"x"1"y"[]null{}"#, mode),
+            ok(&["{}", "{}", "[]", r#""x""#, "1", r#""y""#, "[]", "null", "{}"])
+        );
+        assert_eq!(
+            roots("1 2\n-3\ttrue null", mode),
+            ok(&["1", "2", "-3", "true", "null"])
+        );
+
+        // numbers and literals cannot follow each other directly
+        assert_eq!(
+            roots("truefalse 1[]null", mode),
+            [
+                Ok("true".into()),
+                Err(SyntaxError::MissingSeparator),
+                Ok("false".into()),
+                Ok("1".into()),
+                Ok("[]".into()),
+                Ok("null".into())
+            ]
+        );
+        // but letters are part of an invalid number
+        assert_eq!(roots("1-2", mode), [Err(SyntaxError::InvalidNumber)]);
+        assert_eq!(
+            roots("1null 2", mode),
+            [Err(SyntaxError::InvalidNumber), Ok("2".into())]
+        );
+
+        // the default
+        let mut p = Parser::from_str("[1][2]");
+        assert!(p.next().is_array());
+        assert!(p.next().is_array());
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn whitespace() {
+        let mode = StreamMode::Whitespace;
+        assert_eq!(
+            roots(" 1 [2]\n{}\r\n\"x\" ", mode),
+            ok(&["1", "[]", "{}", r#""x""#])
+        );
+
+        assert_eq!(
+            roots(r#"{}[] "a""b" 3"#, mode),
+            [
+                Ok("{}".into()),
+                Err(SyntaxError::MissingSeparator),
+                Ok("[]".into()),
+                Ok(r#""a""#.into()),
+                Err(SyntaxError::MissingSeparator),
+                Ok(r#""b""#.into()),
+                Ok("3".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn newline_delimited() {
+        let mode = StreamMode::NewlineDelimited;
+        assert_eq!(roots("1\n[2,\n3]\n\n{}\n", mode), ok(&["1", "[]", "{}"]));
+
+        assert_eq!(
+            roots("1 2\n3{}\n", mode),
+            [
+                Ok("1".into()),
+                Err(SyntaxError::MissingSeparator),
+                Ok("2".into()),
+                Ok("3".into()),
+                Err(SyntaxError::MissingSeparator),
+                Ok("{}".into())
+            ]
+        );
+
+        let mut p = Parser::from_str("[1] 2").stream_mode(mode);
+        assert!(p.next().is_array());
+        let err = p.next().unwrap().unwrap_err();
+        assert_eq!(err.position().unwrap().offset(), 4);
+        assert!(!err.is_fatal());
+        assert_eq!(p.next().as_number(), Some(Number::from(2)));
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn single() {
+        let mode = StreamMode::Single;
+        assert_eq!(roots(" [1] ", mode), ok(&["[]"]));
+        assert_eq!(
+            roots("1 2 3", mode),
+            [Ok("1".into()), Err(SyntaxError::TrailingCharacters)]
+        );
+        assert_eq!(roots("", mode), [Err(SyntaxError::EofWhileParsingValue)]);
+    }
+}
+
+mod string_bytes {
+    use super::*;
+    use std::io::{self, BufReader, ErrorKind, Read};
+
+    const TEXT: &str = r#""a,b\n\"c\"\t café 😀 😀 né""#;
+    const DECODED: &str = "a,b\n\"c\"\t café 😀 😀 né";
+
+    #[test]
+    fn copy_whole_string() {
+        let mut p = Parser::from_str(TEXT);
+        let mut out = vec![];
+        let n = io::copy(&mut p.next().as_string().unwrap().read_bytes(), &mut out).unwrap();
+        assert_eq!(n, DECODED.len() as u64);
+        assert_eq!(String::from_utf8(out).unwrap(), DECODED);
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn tiny_reads_and_refills() {
+        for cap in [1, 2, 3, 5] {
+            for chunk in [1, 2, 3, 7] {
+                let mut p = Parser::from_buf_read(BufReader::with_capacity(cap, TEXT.as_bytes()));
+                let mut bytes = p.next().as_string().unwrap().read_bytes();
+                let mut out = vec![];
+                let mut buf = vec![0; chunk];
+                loop {
+                    let n = bytes.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    out.extend_from_slice(&buf[..n]);
+                }
+                assert_eq!(String::from_utf8(out).unwrap(), DECODED, "{cap} {chunk}");
+            }
+        }
+    }
+
+    #[test]
+    fn dropped_early() {
+        let mut p = Parser::from_str(r#"["abc\"def", "ghi"] "#);
+        let mut arr = p.next().as_array().unwrap();
+        {
+            let mut bytes = arr.next().as_string().unwrap().read_bytes();
+            let mut buf = [0; 4];
+            assert_eq!(bytes.read(&mut buf).unwrap(), 3);
+            assert_eq!(bytes.read(&mut buf).unwrap(), 1);
+            assert_eq!(&buf[..1], b"\"");
+        }
+        let ghi = arr.next().as_string().unwrap();
+        assert_eq!(ghi.read_owned().unwrap(), "ghi");
+        assert!(arr.next().is_none());
+    }
+
+    #[test]
+    fn errors() {
+        let mut p = Parser::from_str(r#"["ok\x", 1, "unterminated"#);
+        let mut arr = p.next().as_array().unwrap();
+        {
+            let mut out = vec![];
+            let mut bytes = arr.next().as_string().unwrap().read_bytes();
+            let err = io::copy(&mut bytes, &mut out).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+            let inner = err.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+            assert_eq!(inner.syntax(), Some(SyntaxError::InvalidEscape));
+            assert_eq!(out, b"ok");
+            assert_eq!(bytes.read(&mut [0; 8]).unwrap(), 0);
+        }
+        // the rest of the string was skipped
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+
+        let mut bytes = arr.next().as_string().unwrap().read_bytes();
+        let err = io::copy(&mut bytes, &mut io::sink()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
+
+mod base64 {
+    use super::*;
+    use json_stream::base64::Alphabet;
+    use std::io::{self, BufReader, ErrorKind, Read};
+
+    fn decode(text: &str, alphabet: Alphabet) -> io::Result<Vec<u8>> {
+        let mut p = Parser::from_str(text);
+        let mut out = vec![];
+        let mut bytes = p.next().as_string().unwrap().read_base64(alphabet);
+        bytes.read_to_end(&mut out).map(|_| out)
+    }
+
+    fn syntax(e: io::Error) -> Option<SyntaxError> {
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        e.get_ref()?.downcast_ref::<Error>()?.syntax()
+    }
+
+    #[test]
+    fn alphabets_and_padding() {
+        let cases = [
+            (r#""""#, &b""[..]),
+            (r#""Zg==""#, b"f"),
+            (r#""Zg""#, b"f"),
+            (r#""Zm8=""#, b"fo"),
+            (r#""Zm8""#, b"fo"),
+            (r#""Zm9v""#, b"foo"),
+            (r#""Zm9vYmFy""#, b"foobar"),
+        ];
+        for (text, bytes) in cases {
+            assert_eq!(decode(text, Alphabet::Standard).unwrap(), bytes, "{text}");
+            assert_eq!(decode(text, Alphabet::UrlSafe).unwrap(), bytes, "{text}");
+        }
+
+        let standard = r#""+/+/""#;
+        let url_safe = r#""-_-_""#;
+        let bytes = [0xfb, 0xff, 0xbf];
+        assert_eq!(decode(standard, Alphabet::Standard).unwrap(), bytes);
+        assert_eq!(decode(url_safe, Alphabet::UrlSafe).unwrap(), bytes);
+        // an escaped `/` is still a symbol
+        assert_eq!(decode(r#""+\/+\/""#, Alphabet::Standard).unwrap(), bytes);
+
+        let e = decode(standard, Alphabet::UrlSafe).unwrap_err();
+        assert_eq!(syntax(e), Some(SyntaxError::InvalidBase64));
+        let e = decode(url_safe, Alphabet::Standard).unwrap_err();
+        assert_eq!(syntax(e), Some(SyntaxError::InvalidBase64));
+    }
+
+    #[test]
+    fn tiny_reads_and_refills() {
+        let text = r#""SGVsbG8sIGJhc2U2NCB3b3JsZCE=""#;
+        for cap in [1, 2, 3, 5] {
+            for chunk in [1, 2, 3, 4, 7] {
+                let r = BufReader::with_capacity(cap, text.as_bytes());
+                let mut p = Parser::from_buf_read(r);
+                let mut bytes = p
+                    .next()
+                    .as_string()
+                    .unwrap()
+                    .read_base64(Alphabet::Standard);
+                let mut out = vec![];
+                let mut buf = vec![0; chunk];
+                loop {
+                    let n = bytes.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    out.extend_from_slice(&buf[..n]);
+                }
+                assert_eq!(out, b"Hello, base64 world!", "{cap} {chunk}");
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_padding() {
+        for text in [
+            r#""Z""#,
+            r#""Zm9vY""#,
+            r#""=""#,
+            r#""Z===""#,
+            r#""Zg=""#,
+            r#""Zg=g""#,
+            r#""Zg==Zg==""#,
+        ] {
+            let e = decode(text, Alphabet::Standard).unwrap_err();
+            assert_eq!(syntax(e), Some(SyntaxError::InvalidBase64Padding), "{text}");
+        }
+        let e = decode(r#""Zm9v Zm9v""#, Alphabet::Standard).unwrap_err();
+        assert_eq!(syntax(e), Some(SyntaxError::InvalidBase64));
+    }
+
+    #[test]
+    fn skipped_after_error() {
+        let mut p = Parser::from_str(r#"["Zm9v!Zm9v", "Zm9vYmFy", "Zm9", 1] "#);
+        let mut arr = p.next().as_array().unwrap();
+        {
+            let mut out = vec![];
+            let mut bytes = arr
+                .next()
+                .as_string()
+                .unwrap()
+                .read_base64(Alphabet::Standard);
+            let e = io::copy(&mut bytes, &mut out).unwrap_err();
+            let inner = e.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+            assert_eq!(inner.syntax(), Some(SyntaxError::InvalidBase64));
+            // points at the `!`
+            assert_eq!(inner.position().unwrap().column(), 7);
+            assert_eq!(out, b"foo");
+            assert_eq!(bytes.read(&mut [0; 8]).unwrap(), 0);
+        }
+        {
+            // dropped before the end
+            let mut bytes = arr
+                .next()
+                .as_string()
+                .unwrap()
+                .read_base64(Alphabet::Standard);
+            assert_eq!(bytes.read(&mut [0; 2]).unwrap(), 2);
+        }
+        {
+            // the string ends before an error is found
+            let mut bytes = arr
+                .next()
+                .as_string()
+                .unwrap()
+                .read_base64(Alphabet::Standard);
+            assert_eq!(bytes.read(&mut [0; 8]).unwrap(), 2);
+            assert_eq!(bytes.read(&mut [0; 8]).unwrap(), 0);
+        }
+        assert_eq!(arr.next().as_number(), Some(Number::from(1)));
+        assert!(arr.next().is_none());
+    }
+}
+
+mod key_matching {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn strings() {
+        let mut p = Parser::from_str(r#" "name" "name" "nam" "names" "" "\u0069d" "caf\u00e9s" "#);
+        assert!(p.next().as_string().unwrap().eq_str("name").unwrap());
+        assert!(!p.next().as_string().unwrap().eq_str("nam").unwrap());
+        assert!(!p.next().as_string().unwrap().eq_str("name").unwrap());
+        assert!(p.next().as_string().unwrap().starts_with("name").unwrap());
+        assert!(p.next().as_string().unwrap().eq_str("").unwrap());
+        // escapes are decoded
+        assert!(p.next().as_string().unwrap().eq_str("id").unwrap());
+        assert!(p.next().as_string().unwrap().starts_with("café").unwrap());
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn candidates() {
+        let candidates = ["id", "name", "na", "nam", "names", "na"];
+        let text = r#" "id" "name" "na" "nam" "names" "n" "ids" "x" "" "#;
+        let expected = [
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(4),
+            None,
+            None,
+            None,
+            None,
+        ];
+        for cap in [1, 2, 3, 64] {
+            let mut p = Parser::from_buf_read(BufReader::with_capacity(cap, text.as_bytes()));
+            for want in expected {
+                let s = p.next().as_string().unwrap();
+                assert_eq!(s.match_any(&candidates).unwrap(), want, "{cap}");
+            }
+            assert!(p.next().is_none());
+        }
+        let mut p = Parser::from_str(r#""" "a""#);
+        assert_eq!(p.next().as_string().unwrap().match_any(&[]).unwrap(), None);
+        assert_eq!(p.next().as_string().unwrap().match_any(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn starts_with() {
+        let mut p = Parser::from_str(r#" "x-custom" "x-" "x" "y-x" "abc" "#);
+        assert!(p.next().as_string().unwrap().starts_with("x-").unwrap());
+        assert!(p.next().as_string().unwrap().starts_with("x-").unwrap());
+        assert!(!p.next().as_string().unwrap().starts_with("x-").unwrap());
+        assert!(!p.next().as_string().unwrap().starts_with("x-").unwrap());
+        assert!(p.next().as_string().unwrap().starts_with("").unwrap());
+        assert!(p.next().is_none());
+    }
+
+    #[test]
+    fn object_keys() {
+        let mut p = Parser::from_str(r#"{"id": 1, "x-tag": [2], "name": "a", "other": {}} "#);
+        let mut obj = p.next().as_object().unwrap();
+        let mut found = vec![];
+        while let Some(kv) = obj.next() {
+            let mut kv = kv.unwrap();
+            if let Some(i) = kv.match_any(&["name", "id"]).unwrap() {
+                found.push((i, kv.value().unwrap().as_number().is_some()));
+            }
+        }
+        assert_eq!(found, [(1, true), (0, false)]);
+
+        let mut p = Parser::from_str(r#"{"x-tag": 1, "id": 2} "#);
+        let mut obj = p.next().as_object().unwrap();
+        let mut kv = obj.next().unwrap().unwrap();
+        assert!(kv.starts_with("x-").unwrap());
+        // the key was already read
+        assert!(kv.eq_str("x-tag").is_err());
+        assert_eq!(kv.value().as_number(), Some(Number::from(1)));
+        let mut kv = obj.next().unwrap().unwrap();
+        assert!(kv.eq_str("id").unwrap());
+        assert_eq!(kv.value().as_number(), Some(Number::from(2)));
+        assert!(obj.next().is_none());
+    }
+}
@@ -0,0 +1,556 @@
+#![cfg(feature = "async")]
+
+use json_stream::parse::*;
+
+#[tokio::test]
+async fn example() {
+    let mut p = AsyncParser::new(r#"["a","b","c"]"#.as_bytes());
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+
+    let mut seen: Vec<String> = vec![];
+
+    while let Some(item) = arr.next().await {
+        if let Ok(AsyncJson::String(s)) = item {
+            seen.push(s.read_owned().await.expect("cannot read string"));
+        }
+    }
+
+    assert_eq!(seen, &["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn basics() {
+    let mut p = AsyncParser::new("null true false 0 1 -2 0.5".as_bytes());
+
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Null))));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Bool(true)))));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Bool(false)))));
+    for expected in [
+        Number::from(0),
+        Number::from(1),
+        Number::from(-2),
+        Number::from(0.5),
+    ] {
+        match p.next().await {
+            Some(Ok(AsyncJson::Number(n))) => assert_eq!(n, expected),
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+
+    assert!(p.next().await.is_none());
+}
+
+#[tokio::test]
+async fn chars() {
+    let mut p = AsyncParser::new(r#""a\tbé\u00e9\ud83d\ude00""#.as_bytes());
+
+    let mut chars = match p.next().await {
+        Some(Ok(AsyncJson::String(s))) => s.read_chars(),
+        _ => panic!("expected root value to be a string"),
+    };
+
+    let mut seen = String::new();
+    while let Some(c) = chars.next().await {
+        seen.push(c.unwrap());
+    }
+
+    assert_eq!(seen, "a\tbéé😀");
+}
+
+#[tokio::test]
+async fn object_and_keyval() {
+    let mut p = AsyncParser::new(r#"{"a" : 2, "b":[3, 4], "c": false}"#.as_bytes());
+
+    let mut obj = match p.next().await {
+        Some(Ok(AsyncJson::Object(obj))) => obj,
+        _ => panic!("expected root value to be an object"),
+    };
+
+    let mut kv = obj.next().await.unwrap().unwrap();
+    assert_eq!(kv.key().unwrap().read_owned().await, Ok("a".to_owned()));
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(2)));
+
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Array(_))));
+
+    let mut kv = obj.next().await.unwrap().unwrap();
+    assert_eq!(kv.key().unwrap().read_owned().await, Ok("c".to_owned()));
+    drop(kv);
+
+    assert!(obj.next().await.is_none());
+}
+
+#[tokio::test]
+async fn skipping() {
+    let mut p = AsyncParser::new(
+        r#"[{"a": {"x": [1, "]}"]}, "b": "\"}", "c": 3}, [[2], 3], "skipped"] 4"#.as_bytes(),
+    );
+
+    {
+        let mut arr = match p.next().await {
+            Some(Ok(AsyncJson::Array(arr))) => arr,
+            _ => panic!("expected root value to be an array"),
+        };
+
+        {
+            let mut obj = match arr.next().await {
+                Some(Ok(AsyncJson::Object(obj))) => obj,
+                _ => panic!("expected an object"),
+            };
+            obj.next().await.unwrap().unwrap();
+
+            let mut kv = obj.next().await.unwrap().unwrap();
+            assert_eq!(kv.key().unwrap().read_owned().await, Ok("b".to_owned()));
+            drop(kv);
+
+            let kv = obj.next().await.unwrap().unwrap();
+            assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(3)));
+        }
+
+        let mut sub_arr = match arr.next().await {
+            Some(Ok(AsyncJson::Array(a))) => a,
+            _ => panic!("expected an array"),
+        };
+        assert!(matches!(
+            sub_arr.next().await,
+            Some(Ok(AsyncJson::Array(_)))
+        ));
+    }
+
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(4)));
+    assert!(p.next().await.is_none());
+}
+
+#[tokio::test]
+async fn io_error() {
+    use std::io::{self, ErrorKind};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    struct FailAfter<'a>(&'a [u8]);
+
+    impl AsyncRead for FailAfter<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if self.0.is_empty() {
+                return Poll::Ready(Err(io::Error::new(ErrorKind::ConnectionReset, "reset")));
+            }
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    let mut p = AsyncParser::new(FailAfter(b"[1, [2"));
+    {
+        let mut arr = match p.next().await {
+            Some(Ok(AsyncJson::Array(arr))) => arr,
+            _ => panic!("expected root value to be an array"),
+        };
+        assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(_)))));
+        assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Array(_)))));
+        let err = arr.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            err.io().map(io::Error::kind),
+            Some(ErrorKind::ConnectionReset)
+        );
+        assert!(arr.next().await.is_none());
+    }
+    assert!(p.next().await.is_none());
+}
+
+#[tokio::test]
+async fn error_position() {
+    let mut p = AsyncParser::new("[1,\n  2 3]".as_bytes());
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    arr.next().await;
+    arr.next().await;
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::MissingComma));
+
+    let pos = err.position().unwrap();
+    assert_eq!((pos.offset(), pos.line(), pos.column()), (8, 2, 5));
+}
+
+#[test]
+fn futures_are_send() {
+    fn assert_send<T: Send>(_: T) {}
+
+    let mut p = AsyncParser::new(&b"[1]"[..]);
+    assert_send(p.next());
+}
+
+#[tokio::test]
+async fn string_escapes() {
+    let text = concat!(r#"["a\né😀é", "\x", "c"#, "\t", r#"d", "b"]"#);
+    let mut p = AsyncParser::new(text.as_bytes());
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+
+    let mut seen = vec![];
+    while let Some(item) = arr.next().await {
+        match item {
+            Ok(AsyncJson::String(s)) => seen.push(s.read_owned().await.map_err(|e| e.syntax())),
+            other => panic!("expected a string, got {:?}", other),
+        }
+    }
+    assert_eq!(
+        seen,
+        [
+            Ok("a\né😀é".to_owned()),
+            Err(Some(SyntaxError::InvalidEscape)),
+            Err(Some(SyntaxError::ControlCharacterWhileParsingString)),
+            Ok("b".to_owned())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn number_grammar() {
+    let mut p = AsyncParser::new("[1.5e1, 012, 0x1, 1a, 1.5x, 1e3z, -3]".as_bytes());
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(15.0)));
+    for _ in 0..5 {
+        let err = arr.next().await.unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+    }
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(-3)));
+    assert!(arr.next().await.is_none());
+}
+
+#[tokio::test]
+async fn raw_numbers() {
+    let options = ParserOptions::new().number_mode(NumberMode::Raw);
+    let mut p = AsyncParser::new("[1.000000000000000000001]".as_bytes()).with_options(options);
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    match arr.next().await {
+        Some(Ok(AsyncJson::RawNumber(n))) => assert_eq!(n.as_str(), "1.000000000000000000001"),
+        other => panic!("expected a raw number, got {:?}", other),
+    };
+}
+
+#[tokio::test]
+async fn depth_limit() {
+    let options = ParserOptions::new().max_depth(1);
+    let mut p = AsyncParser::new("[[[1]], 2] 3".as_bytes()).with_options(options);
+
+    {
+        let mut arr = match p.next().await {
+            Some(Ok(AsyncJson::Array(arr))) => arr,
+            _ => panic!("expected root value to be an array"),
+        };
+        let err = arr.next().await.unwrap().unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::RecursionLimitExceeded));
+        assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(2)));
+        assert!(arr.next().await.is_none());
+    }
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(3)));
+}
+
+#[tokio::test]
+async fn lenient_options() {
+    let mut p = AsyncParser::new(r#"{a: [1 2,], "b": 3, c: 4,}"#.as_bytes())
+        .with_options(ParserOptions::lenient());
+
+    {
+        let mut obj = match p.next().await {
+            Some(Ok(AsyncJson::Object(obj))) => obj,
+            _ => panic!("expected root value to be an object"),
+        };
+
+        let mut kv = obj.next().await.unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_owned().await, Ok("a".to_owned()));
+        let mut arr = match kv.value().await {
+            Ok(AsyncJson::Array(arr)) => arr,
+            _ => panic!("expected an array"),
+        };
+        assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(1)));
+        assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(2)));
+        assert!(arr.next().await.is_none());
+        drop(arr);
+
+        obj.next().await.unwrap().unwrap();
+        let kv = obj.next().await.unwrap().unwrap();
+        assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(4)));
+        assert!(obj.next().await.is_none());
+    }
+
+    let mut p = AsyncParser::new("[5,]".as_bytes());
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(_)))));
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::TrailingComma));
+    assert!(arr.next().await.is_none());
+}
+
+#[tokio::test]
+async fn json5_options() {
+    let src = r#"// config
+    {name: 'it\'s', /* skipped */ skip: [']', /* ] */ {}], hex: -0x1F, inf: -Infinity,
+     nan: NaN, bad: / 1, last: 2,}"#;
+    let mut p = AsyncParser::new(src.as_bytes()).with_options(ParserOptions::json5());
+    let mut obj = match p.next().await {
+        Some(Ok(AsyncJson::Object(obj))) => obj,
+        _ => panic!("expected root value to be an object"),
+    };
+
+    let mut kv = obj.next().await.unwrap().unwrap();
+    assert_eq!(kv.key().unwrap().read_owned().await, Ok("name".to_owned()));
+    match kv.value().await {
+        Ok(AsyncJson::String(s)) => assert_eq!(s.read_owned().await, Ok("it's".to_owned())),
+        _ => panic!("expected a string"),
+    }
+    drop(obj.next().await);
+
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(-31)));
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(
+        matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(f64::NEG_INFINITY))
+    );
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n.as_f64().is_nan()));
+
+    let kv = obj.next().await.unwrap().unwrap();
+    let err = kv.value().await.err().unwrap();
+    assert_eq!(err.syntax(), Some(SyntaxError::InvalidComment));
+
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Number(n)) if n == Number::from(2)));
+    assert!(obj.next().await.is_none());
+}
+
+#[tokio::test]
+async fn json5_numbers_and_escapes() {
+    let src = "[+1, .5, 5., +Infinity, 'a\\x41\\\n\\\u{2028}b\\0', '\\01']";
+    let mut p = AsyncParser::new(src.as_bytes()).with_options(ParserOptions::json5());
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+
+    for expected in [1.0, 0.5, 5.0, f64::INFINITY] {
+        assert!(
+            matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(expected))
+        );
+    }
+    match arr.next().await {
+        Some(Ok(AsyncJson::String(s))) => assert_eq!(s.read_owned().await, Ok("aAb\0".to_owned())),
+        _ => panic!("expected a string"),
+    }
+    match arr.next().await {
+        Some(Ok(AsyncJson::String(s))) => {
+            let err = s.read_owned().await.unwrap_err();
+            assert_eq!(err.syntax(), Some(SyntaxError::InvalidEscape));
+        }
+        _ => panic!("expected a string"),
+    }
+    assert!(arr.next().await.is_none());
+}
+
+#[tokio::test]
+async fn malformed_objects() {
+    let mut p = AsyncParser::new(r#"{1: [2], "a" 3, "b": } @"#.as_bytes());
+
+    {
+        let mut obj = match p.next().await {
+            Some(Ok(AsyncJson::Object(obj))) => obj,
+            _ => panic!("expected root value to be an object"),
+        };
+        let err = obj.next().await.unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(SyntaxError::KeyMustBeAString));
+
+        let mut kv = obj.next().await.unwrap().unwrap();
+        assert_eq!(kv.key().unwrap().read_owned().await, Ok("a".to_owned()));
+        assert!(kv.key().is_err());
+        let err = kv.value().await.unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::ExpectedColon));
+
+        let kv = obj.next().await.unwrap().unwrap();
+        let err = kv.value().await.unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::ExpectedSomeValue));
+        assert!(obj.next().await.is_none());
+    }
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::ExpectedSomeValue));
+    assert!(p.next().await.is_none());
+}
+
+#[tokio::test]
+async fn dropped_entries_are_checked() {
+    let mut p = AsyncParser::new(r#"{"a" 1, "b": 2} {"a": @, "b": 3}"#.as_bytes());
+
+    for (code, n) in [
+        (SyntaxError::ExpectedColon, 2),
+        (SyntaxError::ExpectedSomeValue, 3),
+    ] {
+        let mut obj = match p.next().await {
+            Some(Ok(AsyncJson::Object(obj))) => obj,
+            _ => panic!("expected an object"),
+        };
+        drop(obj.next().await.unwrap().unwrap());
+        let err = obj.next().await.unwrap().err().unwrap();
+        assert_eq!(err.syntax(), Some(code));
+
+        let kv = obj.next().await.unwrap().unwrap();
+        assert!(matches!(kv.value().await, Ok(AsyncJson::Number(v)) if v == Number::from(n)));
+        assert!(obj.next().await.is_none());
+    }
+    assert!(p.next().await.is_none());
+}
+
+#[tokio::test]
+async fn resync_after_errors() {
+    let mut p = AsyncParser::new(r#"[{"a": nul}, 1.2.3 [4], 5, "x"#.as_bytes());
+
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    {
+        let mut obj = match arr.next().await {
+            Some(Ok(AsyncJson::Object(obj))) => obj,
+            _ => panic!("expected an object"),
+        };
+        let kv = obj.next().await.unwrap().unwrap();
+        let err = kv.value().await.unwrap_err();
+        assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+        assert!(!err.is_fatal());
+        assert!(obj.next().await.is_none());
+    }
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::InvalidNumber));
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(5)));
+
+    let s = match arr.next().await {
+        Some(Ok(AsyncJson::String(s))) => s,
+        _ => panic!("expected a string"),
+    };
+    let err = s.read_owned().await.unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+    assert!(err.is_fatal());
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+    assert!(arr.next().await.is_none());
+}
+
+#[tokio::test]
+async fn truncated_containers() {
+    let mut p = AsyncParser::new("[1, 2".as_bytes());
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(_)))));
+    assert!(matches!(arr.next().await, Some(Ok(AsyncJson::Number(_)))));
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+    assert!(err.is_fatal());
+    assert!(arr.next().await.is_none());
+
+    let mut p = AsyncParser::new(r#"{"a": 1"#.as_bytes());
+    let mut obj = match p.next().await {
+        Some(Ok(AsyncJson::Object(obj))) => obj,
+        _ => panic!("expected root value to be an object"),
+    };
+    let kv = obj.next().await.unwrap().unwrap();
+    assert!(matches!(kv.value().await, Ok(AsyncJson::Number(_))));
+    let err = match obj.next().await {
+        Some(Err(e)) => e,
+        _ => panic!("expected an error"),
+    };
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+    assert!(err.is_fatal());
+    assert!(obj.next().await.is_none());
+}
+
+#[tokio::test]
+async fn truncated_while_skipping() {
+    let mut p = AsyncParser::new("[1, [2, 3".as_bytes());
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Array(_)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+    assert!(err.is_fatal());
+    assert!(p.next().await.is_none());
+
+    let mut p = AsyncParser::new(r#"{"a": [1"#.as_bytes());
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Object(_)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingObject));
+
+    let mut p = AsyncParser::new(r#""abc"#.as_bytes());
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::String(_)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingString));
+
+    // the rest of an invalid item
+    let mut p = AsyncParser::new("[tru [1, 2".as_bytes());
+    let mut arr = match p.next().await {
+        Some(Ok(AsyncJson::Array(arr))) => arr,
+        _ => panic!("expected root value to be an array"),
+    };
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::InvalidIdentifier));
+    let err = arr.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingList));
+    assert!(err.is_fatal());
+}
+
+#[tokio::test]
+async fn single_document() {
+    let mut p = AsyncParser::new("[1] 2".as_bytes()).single();
+
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Array(_)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::TrailingCharacters));
+    assert!(p.next().await.is_none());
+
+    let mut p = AsyncParser::new(" ".as_bytes()).single();
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::EofWhileParsingValue));
+}
+
+#[tokio::test]
+async fn stream_modes() {
+    let mut p = AsyncParser::new(r#"{"a":1}[2]3 truenull"#.as_bytes());
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Object(_)))));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Array(_)))));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(3)));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Bool(true)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::MissingSeparator));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Null))));
+    assert!(p.next().await.is_none());
+
+    let mut p = AsyncParser::new("1 2\n3".as_bytes()).stream_mode(StreamMode::NewlineDelimited);
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(_)))));
+    let err = p.next().await.unwrap().unwrap_err();
+    assert_eq!(err.syntax(), Some(SyntaxError::MissingSeparator));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(2)));
+    assert!(matches!(p.next().await, Some(Ok(AsyncJson::Number(n))) if n == Number::from(3)));
+    assert!(p.next().await.is_none());
+}